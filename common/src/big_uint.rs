@@ -0,0 +1,304 @@
+use std::cmp::Ordering;
+use std::fmt::Display;
+use std::ops::{Add, AddAssign, Mul};
+use std::str::FromStr;
+
+// Arbitrary-precision unsigned integer, stored as base 2^32 limbs from the
+// least significant one. There are never leading zero limbs, zero being
+// represented without limbs.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct BigUint {
+    limbs: Vec<u32>,
+}
+
+impl BigUint {
+    pub fn zero() -> Self {
+        Self { limbs: vec![] }
+    }
+
+    pub fn one() -> Self {
+        Self::from(1u64)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.limbs.is_empty()
+    }
+
+    fn normalize(mut self) -> Self {
+        while self.limbs.last() == Some(&0) {
+            self.limbs.pop();
+        }
+        self
+    }
+
+    pub fn to_u64(&self) -> Option<u64> {
+        match self.limbs.as_slice() {
+            [] => Some(0),
+            [low] => Some(*low as u64),
+            [low, high] => Some(((*high as u64) << 32) | *low as u64),
+            _ => None,
+        }
+    }
+
+    pub fn nb_bits(&self) -> usize {
+        match self.limbs.last() {
+            Some(high) => self.limbs.len() * 32 - high.leading_zeros() as usize,
+            None => 0,
+        }
+    }
+
+    fn bit(&self, idx: usize) -> bool {
+        self.limbs
+            .get(idx / 32)
+            .is_some_and(|limb| (limb >> (idx % 32)) & 1 == 1)
+    }
+
+    fn shl1_in_place(&mut self) {
+        let mut carry = 0;
+        for limb in self.limbs.iter_mut() {
+            let next_carry = *limb >> 31;
+            *limb = (*limb << 1) | carry;
+            carry = next_carry;
+        }
+        if carry != 0 {
+            self.limbs.push(carry);
+        }
+    }
+
+    fn sub_in_place(&mut self, other: &Self) {
+        let mut borrow = 0i64;
+        for (idx, limb) in self.limbs.iter_mut().enumerate() {
+            let mut diff = *limb as i64 - borrow - *other.limbs.get(idx).unwrap_or(&0) as i64;
+            borrow = 0;
+            if diff < 0 {
+                diff += 1 << 32;
+                borrow = 1;
+            }
+            *limb = diff as u32;
+        }
+        assert_eq!(borrow, 0, "BigUint subtraction underflow");
+        while self.limbs.last() == Some(&0) {
+            self.limbs.pop();
+        }
+    }
+
+    pub fn checked_sub(&self, other: &Self) -> Option<Self> {
+        if self < other {
+            return None;
+        }
+        let mut result = self.clone();
+        result.sub_in_place(other);
+        Some(result)
+    }
+
+    fn mul_small_add(&mut self, factor: u32, addend: u32) {
+        let mut carry = addend as u64;
+        for limb in self.limbs.iter_mut() {
+            let product = *limb as u64 * factor as u64 + carry;
+            *limb = product as u32;
+            carry = product >> 32;
+        }
+        if carry != 0 {
+            self.limbs.push(carry as u32);
+        }
+    }
+
+    // Divides in place by a single limb and returns the remainder.
+    fn div_rem_small_in_place(&mut self, divisor: u32) -> u32 {
+        let mut remainder = 0u64;
+        for limb in self.limbs.iter_mut().rev() {
+            let current = (remainder << 32) | *limb as u64;
+            *limb = (current / divisor as u64) as u32;
+            remainder = current % divisor as u64;
+        }
+        while self.limbs.last() == Some(&0) {
+            self.limbs.pop();
+        }
+        remainder as u32
+    }
+
+    // Quotient and remainder of the division, `None` when dividing by zero.
+    pub fn checked_div_rem(&self, divisor: &Self) -> Option<(Self, Self)> {
+        if divisor.is_zero() {
+            return None;
+        }
+        if let [small] = divisor.limbs.as_slice() {
+            let mut quotient = self.clone();
+            let remainder = quotient.div_rem_small_in_place(*small);
+            return Some((quotient, Self::from(remainder as u64)));
+        }
+
+        // Schoolbook binary long division.
+        let nb_bits = self.nb_bits();
+        let mut quotient = Self {
+            limbs: vec![0; nb_bits.div_ceil(32)],
+        };
+        let mut remainder = Self::zero();
+        for idx in (0..nb_bits).rev() {
+            remainder.shl1_in_place();
+            if self.bit(idx) {
+                match remainder.limbs.first_mut() {
+                    Some(low) => *low |= 1,
+                    None => remainder.limbs.push(1),
+                }
+            }
+            if remainder >= *divisor {
+                remainder.sub_in_place(divisor);
+                quotient.limbs[idx / 32] |= 1 << (idx % 32);
+            }
+        }
+        Some((quotient.normalize(), remainder))
+    }
+
+    pub fn pow(&self, mut exponent: u32) -> Self {
+        let mut result = Self::one();
+        let mut base = self.clone();
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result = &result * &base;
+            }
+            exponent >>= 1;
+            if exponent > 0 {
+                base = &base * &base;
+            }
+        }
+        result
+    }
+
+    pub fn gcd(&self, other: &Self) -> Self {
+        let (mut a, mut b) = (self.clone(), other.clone());
+        while let Some((_, remainder)) = a.checked_div_rem(&b) {
+            a = b;
+            b = remainder;
+        }
+        a
+    }
+}
+
+impl From<u64> for BigUint {
+    fn from(value: u64) -> Self {
+        Self {
+            limbs: vec![value as u32, (value >> 32) as u32],
+        }
+        .normalize()
+    }
+}
+
+impl Ord for BigUint {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.limbs
+            .len()
+            .cmp(&other.limbs.len())
+            .then_with(|| self.limbs.iter().rev().cmp(other.limbs.iter().rev()))
+    }
+}
+
+impl PartialOrd for BigUint {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl AddAssign<&BigUint> for BigUint {
+    fn add_assign(&mut self, other: &BigUint) {
+        if self.limbs.len() < other.limbs.len() {
+            self.limbs.resize(other.limbs.len(), 0);
+        }
+        let mut carry = 0u64;
+        for (idx, limb) in self.limbs.iter_mut().enumerate() {
+            let sum = *limb as u64 + *other.limbs.get(idx).unwrap_or(&0) as u64 + carry;
+            *limb = sum as u32;
+            carry = sum >> 32;
+        }
+        if carry != 0 {
+            self.limbs.push(carry as u32);
+        }
+    }
+}
+
+impl Add<&BigUint> for &BigUint {
+    type Output = BigUint;
+
+    fn add(self, other: &BigUint) -> BigUint {
+        let mut result = self.clone();
+        result += other;
+        result
+    }
+}
+
+impl Mul<&BigUint> for &BigUint {
+    type Output = BigUint;
+
+    fn mul(self, other: &BigUint) -> BigUint {
+        if self.is_zero() || other.is_zero() {
+            return BigUint::zero();
+        }
+        let mut limbs = vec![0u32; self.limbs.len() + other.limbs.len()];
+        for (i, &a) in self.limbs.iter().enumerate() {
+            let mut carry = 0u64;
+            for (j, &b) in other.limbs.iter().enumerate() {
+                let product = a as u64 * b as u64 + limbs[i + j] as u64 + carry;
+                limbs[i + j] = product as u32;
+                carry = product >> 32;
+            }
+            limbs[i + other.limbs.len()] = carry as u32;
+        }
+        BigUint { limbs }.normalize()
+    }
+}
+
+impl std::iter::Sum for BigUint {
+    fn sum<I: Iterator<Item = BigUint>>(iter: I) -> Self {
+        let mut total = BigUint::zero();
+        for value in iter {
+            total += &value;
+        }
+        total
+    }
+}
+
+impl Display for BigUint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_zero() {
+            return f.pad_integral(true, "", "0");
+        }
+        // Peel off nine decimal digits at a time.
+        let mut chunks: Vec<u32> = vec![];
+        let mut value = self.clone();
+        while !value.is_zero() {
+            chunks.push(value.div_rem_small_in_place(1_000_000_000));
+        }
+        let mut digits = chunks.pop().unwrap_or(0).to_string();
+        for chunk in chunks.iter().rev() {
+            digits.push_str(&format!("{chunk:09}"));
+        }
+        f.pad_integral(true, "", &digits)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseBigUintError;
+
+impl Display for ParseBigUintError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid digit found in string")
+    }
+}
+
+impl FromStr for BigUint {
+    type Err = ParseBigUintError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() {
+            return Err(ParseBigUintError);
+        }
+        let mut value = BigUint::zero();
+        for c in s.chars() {
+            let Some(digit) = c.to_digit(10) else {
+                return Err(ParseBigUintError);
+            };
+            value.mul_small_add(10, digit);
+        }
+        Ok(value.normalize())
+    }
+}
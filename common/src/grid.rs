@@ -0,0 +1,246 @@
+use std::fmt::Display;
+use std::ops::{Index, IndexMut, Range};
+
+const DIRECTIONS_4: [(isize, isize); 4] = [
+    (-1, 0), // top-mid
+    (0, -1), // mid-left
+    (0, 1),  // mid-right
+    (1, 0),  // bottom-mid
+];
+
+const DIRECTIONS_8: [(isize, isize); 8] = [
+    (-1, -1), // top-left
+    (-1, 0),  // top-mid
+    (-1, 1),  // top-right
+    (0, -1),  // mid-left
+    (0, 1),   // mid-right
+    (1, -1),  // bottom-left
+    (1, 0),   // bottom-mid
+    (1, 1),   // bottom-right
+];
+
+// Dense row-major grid, cells are addressed as (row, col).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    pub fn from_vec(width: usize, height: usize, cells: Vec<T>) -> Self {
+        assert_eq!(
+            cells.len(),
+            width * height,
+            "Grid of {width}x{height} can't be built from {} cells",
+            cells.len()
+        );
+        Self {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    // Build a grid from a char map, one line per row. Lines shorter than the
+    // longest one are padded with `pad`.
+    pub fn parse<S: AsRef<str>>(lines: &[S], pad: T, mut f: impl FnMut(char) -> T) -> Self
+    where
+        T: Clone,
+    {
+        let width = lines
+            .iter()
+            .map(|l| l.as_ref().chars().count())
+            .max()
+            .unwrap_or(0);
+        let height = lines.len();
+        let mut cells = Vec::with_capacity(width * height);
+        for line in lines {
+            let mut nb_chars = 0;
+            for c in line.as_ref().chars() {
+                cells.push(f(c));
+                nb_chars += 1;
+            }
+            cells.extend(std::iter::repeat_n(pad.clone(), width - nb_chars));
+        }
+        Self::from_vec(width, height, cells)
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn in_bounds(&self, row: isize, col: isize) -> bool {
+        row >= 0 && col >= 0 && (row as usize) < self.height && (col as usize) < self.width
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> Option<&T> {
+        if row < self.height && col < self.width {
+            Some(&self.cells[row * self.width + col])
+        } else {
+            None
+        }
+    }
+
+    pub fn get_mut(&mut self, row: usize, col: usize) -> Option<&mut T> {
+        if row < self.height && col < self.width {
+            Some(&mut self.cells[row * self.width + col])
+        } else {
+            None
+        }
+    }
+
+    fn offset(&self, row: usize, col: usize, dr: isize, dc: isize) -> Option<(usize, usize)> {
+        let new_row = row as isize + dr;
+        let new_col = col as isize + dc;
+        if self.in_bounds(new_row, new_col) {
+            Some((new_row as usize, new_col as usize))
+        } else {
+            None
+        }
+    }
+
+    // Positions of the orthogonal neighbours of (row, col) inside the grid.
+    pub fn neighbours4(&self, row: usize, col: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+        DIRECTIONS_4
+            .iter()
+            .filter_map(move |&(dr, dc)| self.offset(row, col, dr, dc))
+    }
+
+    // Positions of the orthogonal and diagonal neighbours of (row, col) inside
+    // the grid.
+    pub fn neighbours8(&self, row: usize, col: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+        DIRECTIONS_8
+            .iter()
+            .filter_map(move |&(dr, dc)| self.offset(row, col, dr, dc))
+    }
+
+    pub fn row(&self, row: usize) -> &[T] {
+        &self.cells[row * self.width..(row + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        // `chunks` panics on a zero chunk size, an empty row still yields
+        // nothing since there are no cells.
+        self.cells.chunks(self.width.max(1))
+    }
+
+    pub fn column(&self, col: usize) -> impl Iterator<Item = &T> {
+        assert!(col < self.width, "Column {col} out of bounds");
+        self.cells.iter().skip(col).step_by(self.width)
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.width).map(move |col| self.column(col))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = ((usize, usize), &T)> {
+        self.cells
+            .iter()
+            .enumerate()
+            .map(|(idx, cell)| ((idx / self.width, idx % self.width), cell))
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = ((usize, usize), &mut T)> {
+        let width = self.width;
+        self.cells
+            .iter_mut()
+            .enumerate()
+            .map(move |(idx, cell)| ((idx / width, idx % width), cell))
+    }
+
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+
+    // Copy of the cells within the given rows and columns.
+    pub fn sub_grid(&self, rows: Range<usize>, cols: Range<usize>) -> Self
+    where
+        T: Clone,
+    {
+        assert!(
+            rows.end <= self.height && cols.end <= self.width,
+            "Sub grid {rows:?}x{cols:?} out of grid of {}x{}",
+            self.width,
+            self.height
+        );
+        let mut cells = Vec::with_capacity(rows.len() * cols.len());
+        for row in rows.clone() {
+            cells.extend_from_slice(&self.row(row)[cols.clone()]);
+        }
+        Self {
+            width: cols.len(),
+            height: rows.len(),
+            cells,
+        }
+    }
+
+    pub fn transpose(&self) -> Self
+    where
+        T: Clone,
+    {
+        let mut cells = Vec::with_capacity(self.cells.len());
+        for col in 0..self.width {
+            cells.extend(self.column(col).cloned());
+        }
+        Self {
+            width: self.height,
+            height: self.width,
+            cells,
+        }
+    }
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn new(width: usize, height: usize, fill: T) -> Self {
+        Self {
+            width,
+            height,
+            cells: vec![fill; width * height],
+        }
+    }
+}
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, (row, col): (usize, usize)) -> &Self::Output {
+        let Some(cell) = self.get(row, col) else {
+            panic!(
+                "Position ({row}, {col}) out of grid of {}x{}",
+                self.width, self.height
+            );
+        };
+        cell
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, (row, col): (usize, usize)) -> &mut Self::Output {
+        let (width, height) = (self.width, self.height);
+        let Some(cell) = self.get_mut(row, col) else {
+            panic!("Position ({row}, {col}) out of grid of {width}x{height}");
+        };
+        cell
+    }
+}
+
+impl<T: Display> Display for Grid<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for row in self.rows() {
+            for cell in row {
+                write!(f, "{cell}")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,5 @@
+pub mod big_uint;
+pub mod grid;
+
+pub use big_uint::BigUint;
+pub use grid::Grid;
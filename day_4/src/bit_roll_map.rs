@@ -0,0 +1,142 @@
+// Roll map storing one bit per position, each row being padded to a whole
+// number of 64-bit words. Column `col` of a row is bit `col % 64` of word
+// `col / 64`.
+pub struct BitRollMap {
+    width: usize,
+    height: usize,
+    words_per_row: usize,
+    words: Vec<u64>,
+}
+
+impl BitRollMap {
+    pub fn parse(lines: &[String]) -> Self {
+        let width = lines.iter().map(|l| l.len()).max().unwrap_or(0);
+        let height = lines.len();
+        let words_per_row = width.div_ceil(64);
+        let mut words = vec![0u64; words_per_row * height];
+
+        for (row, line) in lines.iter().enumerate() {
+            for (col, c) in line.bytes().enumerate() {
+                if c == b'@' {
+                    words[row * words_per_row + col / 64] |= 1 << (col % 64);
+                }
+            }
+        }
+
+        Self {
+            width,
+            height,
+            words_per_row,
+            words,
+        }
+    }
+
+    fn row(&self, row: usize) -> &[u64] {
+        &self.words[row * self.words_per_row..(row + 1) * self.words_per_row]
+    }
+
+    // Masks out the padding bits of the last word of a row.
+    fn word_mask(&self, word_idx: usize) -> u64 {
+        let nb_bits = self.width - word_idx * 64;
+        if nb_bits >= 64 {
+            u64::MAX
+        } else {
+            (1 << nb_bits) - 1
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn nb_paper_rolls(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    // Computes, for a whole row at once, which rolls have fewer than four rolls
+    // in the eight adjacent positions.
+    //
+    // The eight neighbour bitsets are obtained by shifting the rows above, at
+    // and below by one column each way, then summed column-wise with a bit-
+    // sliced counter: `counter[k]` holds bit k of the number of neighbours of
+    // each column. Only bits 2 and 3 matter, a count is below four if both are
+    // unset.
+    fn accessible_in_row(&self, row: usize, accessible: &mut [u64]) {
+        let current = self.row(row);
+        let above = (row > 0).then(|| self.row(row - 1));
+        let below = (row + 1 < self.height).then(|| self.row(row + 1));
+
+        for word_idx in 0..self.words_per_row {
+            let mut counter = [0u64; 4];
+
+            let (from_left, from_right) = self.shifted(current, word_idx);
+            add_to_counter(&mut counter, from_left);
+            add_to_counter(&mut counter, from_right);
+
+            for line in [above, below].into_iter().flatten() {
+                let (from_left, from_right) = self.shifted(line, word_idx);
+                add_to_counter(&mut counter, from_left);
+                add_to_counter(&mut counter, line[word_idx]);
+                add_to_counter(&mut counter, from_right);
+            }
+
+            accessible[word_idx] =
+                current[word_idx] & !(counter[2] | counter[3]) & self.word_mask(word_idx);
+        }
+    }
+
+    // Returns the word of the row shifted by one column each way: bit `col` of
+    // the first word holds the roll at `col - 1`, bit `col` of the second the
+    // roll at `col + 1`.
+    fn shifted(&self, line: &[u64], word_idx: usize) -> (u64, u64) {
+        let word = line[word_idx];
+        let mut from_left = word << 1;
+        if word_idx > 0 {
+            from_left |= line[word_idx - 1] >> 63;
+        }
+        let mut from_right = word >> 1;
+        if word_idx + 1 < self.words_per_row {
+            from_right |= line[word_idx + 1] << 63;
+        }
+        (from_left, from_right)
+    }
+
+    // Removes every accessible roll at once, wave after wave, until none is
+    // accessible anymore. Returns the number of rolls removed per wave.
+    pub fn remove_paper_rolls_by_wave(&mut self) -> Vec<usize> {
+        let mut paper_rolls_removed_per_wave: Vec<usize> = vec![];
+        let mut accessible = vec![0u64; self.words.len()];
+
+        loop {
+            for row in 0..self.height {
+                let range = row * self.words_per_row..(row + 1) * self.words_per_row;
+                self.accessible_in_row(row, &mut accessible[range]);
+            }
+
+            let nb_removed: usize = accessible.iter().map(|w| w.count_ones() as usize).sum();
+            if nb_removed == 0 {
+                break;
+            }
+
+            for (word, removed) in self.words.iter_mut().zip(&accessible) {
+                *word &= !removed;
+            }
+            paper_rolls_removed_per_wave.push(nb_removed);
+        }
+
+        paper_rolls_removed_per_wave
+    }
+}
+
+fn add_to_counter(counter: &mut [u64; 4], bits: u64) {
+    let mut carry = bits;
+    for digit in counter.iter_mut() {
+        let next_carry = *digit & carry;
+        *digit ^= carry;
+        carry = next_carry;
+    }
+}
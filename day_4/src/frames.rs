@@ -0,0 +1,124 @@
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use common::Grid;
+
+use crate::PaperRollPlaceHolder;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    // Binary portable pixmap, removable rolls are shown in red.
+    Ppm,
+    // Binary portable graymap, removable rolls are shown in mid-gray.
+    Pgm,
+}
+
+impl ImageFormat {
+    fn extension(&self) -> &'static str {
+        match self {
+            ImageFormat::Ppm => "ppm",
+            ImageFormat::Pgm => "pgm",
+        }
+    }
+
+    fn header(&self) -> &'static str {
+        match self {
+            ImageFormat::Ppm => "P6",
+            ImageFormat::Pgm => "P5",
+        }
+    }
+
+    fn pixel(&self, paper_roll: &PaperRollPlaceHolder) -> &'static [u8] {
+        match (self, paper_roll.has_paper_roll(), paper_roll.is_removable()) {
+            (ImageFormat::Ppm, false, _) => &[255, 255, 255],
+            (ImageFormat::Ppm, true, false) => &[60, 60, 60],
+            (ImageFormat::Ppm, true, true) => &[220, 30, 30],
+            (ImageFormat::Pgm, false, _) => &[255],
+            (ImageFormat::Pgm, true, false) => &[0],
+            (ImageFormat::Pgm, true, true) => &[128],
+        }
+    }
+}
+
+// Writes one image per wave in a directory, each cell of the grid being drawn
+// as a square of `scale` x `scale` pixels.
+pub struct FrameExporter {
+    directory: PathBuf,
+    format: ImageFormat,
+    scale: usize,
+}
+
+impl FrameExporter {
+    pub fn new(directory: &Path, format: ImageFormat, scale: usize) -> Self {
+        if std::fs::create_dir_all(directory).is_err() {
+            panic!("Failed to create directory: `{}`", directory.display());
+        }
+        Self {
+            directory: directory.to_path_buf(),
+            format,
+            scale: scale.max(1),
+        }
+    }
+
+    pub fn write_frame(&self, iteration: usize, paper_rolls_grid: &Grid<PaperRollPlaceHolder>) {
+        let path = self.directory.join(format!(
+            "iteration_{:04}.{}",
+            iteration,
+            self.format.extension()
+        ));
+
+        let mut image: Vec<u8> = format!(
+            "{}\n{} {}\n255\n",
+            self.format.header(),
+            paper_rolls_grid.width() * self.scale,
+            paper_rolls_grid.height() * self.scale
+        )
+        .into_bytes();
+        for row in paper_rolls_grid.rows() {
+            for _ in 0..self.scale {
+                for paper_roll in row {
+                    for _ in 0..self.scale {
+                        image.extend_from_slice(self.format.pixel(paper_roll));
+                    }
+                }
+            }
+        }
+
+        if std::fs::write(&path, image).is_err() {
+            panic!("Failed to write frame: `{}`", path.display());
+        }
+        println!("Iteration {iteration} written to {}", path.display());
+    }
+}
+
+// Redraws the grid in place in the terminal, then waits for `delay` so that
+// the successive waves look like an animation.
+pub fn print_ansi_frame(
+    iteration: usize,
+    paper_rolls_grid: &Grid<PaperRollPlaceHolder>,
+    delay: Duration,
+) {
+    let mut frame = String::from("\x1b[2J\x1b[H");
+    frame.push_str(&format!("Iteration {iteration}\n"));
+    for row in paper_rolls_grid.rows() {
+        for paper_roll in row {
+            if !paper_roll.has_paper_roll() {
+                frame.push('.');
+            } else if paper_roll.is_removable() {
+                frame.push_str("\x1b[1;31mx\x1b[0m");
+            } else {
+                frame.push('@');
+            }
+        }
+        frame.push('\n');
+    }
+
+    let mut stdout = std::io::stdout().lock();
+    if stdout.write_all(frame.as_bytes()).is_err() || stdout.flush().is_err() {
+        panic!("Failed to print iteration {iteration}");
+    }
+    std::thread::sleep(delay);
+}
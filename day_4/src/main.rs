@@ -198,11 +198,23 @@
 // Start with your original diagram. How many rolls of paper in total can be
 // removed by the Elves and their forklifts?
 
-use std::io::BufRead;
+mod bit_roll_map;
+mod frames;
+mod sparse_roll_map;
 
+use std::{fmt::Display, io::BufRead, path::PathBuf, time::Duration};
+
+use bit_roll_map::BitRollMap;
+use common::Grid;
+use frames::{FrameExporter, ImageFormat};
+use sparse_roll_map::SparseRollMap;
+
+#[derive(Clone)]
 struct PaperRollPlaceHolder {
     status: bool,
     removable: bool,
+    nb_adjacent_rolls: usize,
+    removed_at_wave: Option<usize>,
 }
 
 impl PaperRollPlaceHolder {
@@ -210,6 +222,8 @@ impl PaperRollPlaceHolder {
         Self {
             status,
             removable: false,
+            nb_adjacent_rolls: 0,
+            removed_at_wave: None,
         }
     }
 
@@ -225,107 +239,579 @@ impl PaperRollPlaceHolder {
         self.removable = true;
     }
 
-    fn remove(&mut self) {
+    fn removed_at_wave(&self) -> Option<usize> {
+        self.removed_at_wave
+    }
+
+    fn remove(&mut self, wave: usize) {
         self.status = false;
         self.removable = false;
+        self.removed_at_wave = Some(wave);
     }
 }
 
-fn can_remove_paper_rolls(
-    paper_rolls_grid: &mut Vec<Vec<PaperRollPlaceHolder>>,
-    total_paper_rolls_removed: &mut u32,
-) -> bool {
-    let mut has_paper_roll_to_remove: bool = false;
-
-    let directions = [
-        (-1, -1), // top-left
-        (-1, 0),  // top-mid
-        (-1, 1),  // top-right
-        (0, -1),  // mid-left
-        (0, 1),   // mid-right
-        (1, -1),  // bottom-left
-        (1, 0),   // bottom-mid
-        (1, 1),   // bottom-right
-    ];
-
-    let rows = paper_rolls_grid.len();
-
-    for row in 0..rows {
-        let cols = paper_rolls_grid[row].len();
-        for col in 0..cols {
-            if !paper_rolls_grid[row][col].has_paper_roll() {
-                print!(".");
-                continue;
+impl Display for PaperRollPlaceHolder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let c = if !self.has_paper_roll() {
+            '.'
+        } else if self.is_removable() {
+            'x'
+        } else {
+            '@'
+        };
+        write!(f, "{c}")
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Neighbourhood {
+    // All the cells within a square of the given radius.
+    Moore(usize),
+    // All the cells within the given Manhattan distance.
+    VonNeumann(usize),
+    Custom(Vec<(isize, isize)>),
+}
+
+impl Neighbourhood {
+    fn offsets(&self) -> Vec<(isize, isize)> {
+        match self {
+            Neighbourhood::Moore(radius) | Neighbourhood::VonNeumann(radius) => {
+                let radius = *radius as isize;
+                let mut offsets = vec![];
+                for dr in -radius..=radius {
+                    for dc in -radius..=radius {
+                        if (dr, dc) == (0, 0) {
+                            continue;
+                        }
+                        if matches!(self, Neighbourhood::VonNeumann(_))
+                            && dr.abs() + dc.abs() > radius
+                        {
+                            continue;
+                        }
+                        offsets.push((dr, dc));
+                    }
+                }
+                offsets
             }
+            Neighbourhood::Custom(offsets) => offsets.clone(),
+        }
+    }
+}
 
-            let mut nb_adjacent_rolls = 0;
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Boundary {
+    // Positions outside the grid are empty.
+    Walls,
+    // The grid wraps around on both axes.
+    Toroidal,
+}
 
-            for (dr, dc) in directions {
-                let new_row = row as i32 + dr;
-                let new_col = col as i32 + dc;
+// A roll of paper is accessible if fewer than `threshold` rolls are found in
+// its neighbourhood.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct AccessibilityRule {
+    offsets: Vec<(isize, isize)>,
+    // Negated offsets: the rolls whose neighbourhood includes a position.
+    // Custom neighbourhoods need not be symmetric.
+    reverse_offsets: Vec<(isize, isize)>,
+    threshold: usize,
+    boundary: Boundary,
+}
 
-                // Check bounds
-                if new_row >= 0 && new_row < rows as i32 && new_col >= 0 && new_col < cols as i32 {
-                    nb_adjacent_rolls += paper_rolls_grid[new_row as usize][new_col as usize]
-                        .has_paper_roll() as u32;
+impl AccessibilityRule {
+    fn new(neighbourhood: Neighbourhood, threshold: usize, boundary: Boundary) -> Self {
+        let offsets = neighbourhood.offsets();
+        let reverse_offsets = offsets.iter().map(|&(dr, dc)| (-dr, -dc)).collect();
+        Self {
+            offsets,
+            reverse_offsets,
+            threshold,
+            boundary,
+        }
+    }
+
+    fn neighbours<T>(&self, grid: &Grid<T>, row: usize, col: usize) -> Vec<(usize, usize)> {
+        self.shifted_positions(&self.offsets, grid, row, col)
+    }
+
+    // Positions of the rolls counting (row, col) among their neighbours.
+    fn dependents<T>(&self, grid: &Grid<T>, row: usize, col: usize) -> Vec<(usize, usize)> {
+        self.shifted_positions(&self.reverse_offsets, grid, row, col)
+    }
+
+    fn shifted_positions<T>(
+        &self,
+        offsets: &[(isize, isize)],
+        grid: &Grid<T>,
+        row: usize,
+        col: usize,
+    ) -> Vec<(usize, usize)> {
+        let height = grid.height() as isize;
+        let width = grid.width() as isize;
+        offsets
+            .iter()
+            .filter_map(|&(dr, dc)| {
+                let new_row = row as isize + dr;
+                let new_col = col as isize + dc;
+                match self.boundary {
+                    Boundary::Walls => grid
+                        .in_bounds(new_row, new_col)
+                        .then_some((new_row as usize, new_col as usize)),
+                    Boundary::Toroidal => Some((
+                        new_row.rem_euclid(height) as usize,
+                        new_col.rem_euclid(width) as usize,
+                    )),
+                }
+            })
+            .collect()
+    }
+}
+
+impl Default for AccessibilityRule {
+    // The forklifts can only access a roll of paper if there are fewer than
+    // four rolls of paper in the eight adjacent positions.
+    fn default() -> Self {
+        Self::new(Neighbourhood::Moore(1), 4, Boundary::Walls)
+    }
+}
+
+// Removes the paper rolls wave by wave: every roll accessible at the start of
+// a wave is removed at once, like the Elves do. Instead of rescanning the whole
+// grid, the number of adjacent rolls is kept up to date for each roll and only
+// the rolls counting the removed ones are examined to build the next wave.
+struct ForkliftSimulation {
+    paper_rolls_grid: Grid<PaperRollPlaceHolder>,
+    rule: AccessibilityRule,
+    accessible_paper_rolls: Vec<(usize, usize)>,
+    nb_waves: usize,
+}
+
+impl ForkliftSimulation {
+    fn new(mut paper_rolls_grid: Grid<PaperRollPlaceHolder>, rule: AccessibilityRule) -> Self {
+        let mut accessible_paper_rolls: Vec<(usize, usize)> = vec![];
+
+        for row in 0..paper_rolls_grid.height() {
+            for col in 0..paper_rolls_grid.width() {
+                if !paper_rolls_grid[(row, col)].has_paper_roll() {
+                    continue;
+                }
+
+                let nb_adjacent_rolls = rule
+                    .neighbours(&paper_rolls_grid, row, col)
+                    .into_iter()
+                    .filter(|&pos| paper_rolls_grid[pos].has_paper_roll())
+                    .count();
+
+                let paper_roll = &mut paper_rolls_grid[(row, col)];
+                paper_roll.nb_adjacent_rolls = nb_adjacent_rolls;
+                if nb_adjacent_rolls < rule.threshold {
+                    paper_roll.mark_removable();
+                    accessible_paper_rolls.push((row, col));
                 }
             }
+        }
+
+        Self {
+            paper_rolls_grid,
+            rule,
+            accessible_paper_rolls,
+            nb_waves: 0,
+        }
+    }
+
+    fn grid(&self) -> &Grid<PaperRollPlaceHolder> {
+        &self.paper_rolls_grid
+    }
+
+    fn accessible_paper_rolls(&self) -> &[(usize, usize)] {
+        &self.accessible_paper_rolls
+    }
+
+    // Removes the current wave and returns the number of paper rolls removed.
+    fn remove_accessible_paper_rolls(&mut self) -> usize {
+        let wave = std::mem::take(&mut self.accessible_paper_rolls);
+        self.nb_waves += 1;
+
+        for &pos in &wave {
+            self.paper_rolls_grid[pos].remove(self.nb_waves);
+        }
 
-            if nb_adjacent_rolls < 4 {
-                print!("x");
-                paper_rolls_grid[row][col].mark_removable();
-                *total_paper_rolls_removed += 1;
-                has_paper_roll_to_remove = true;
-            } else {
-                print!("@");
+        for &(row, col) in &wave {
+            for pos in self.rule.dependents(&self.paper_rolls_grid, row, col) {
+                let paper_roll = &mut self.paper_rolls_grid[pos];
+                if !paper_roll.has_paper_roll() {
+                    continue;
+                }
+                paper_roll.nb_adjacent_rolls -= 1;
+                if paper_roll.nb_adjacent_rolls < self.rule.threshold && !paper_roll.is_removable()
+                {
+                    paper_roll.mark_removable();
+                    self.accessible_paper_rolls.push(pos);
+                }
             }
         }
-        println!();
+
+        wave.len()
     }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum OutputMode {
+    // Print the grid at each iteration, marking the rolls about to be removed.
+    Iterations,
+    // Print the wave in which each roll is removed, the number of removals per
+    // wave and the rolls that can never be removed.
+    DepthMap,
+    // Write the grid at each iteration as an image in a directory.
+    Frames {
+        directory: PathBuf,
+        format: ImageFormat,
+        scale: usize,
+    },
+    // Redraw the grid at each iteration in the terminal.
+    Animation(Duration),
+}
 
-    has_paper_roll_to_remove
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Engine {
+    // One `PaperRollPlaceHolder` per position, supports every rule and output.
+    Grid,
+    // One bit per position, only supports the default rule and only reports
+    // the number of rolls removed per wave.
+    BitPacked,
+    // Set of roll positions without bounds, does not support wrap-around and
+    // only reports the number of rolls removed per wave.
+    Sparse,
 }
 
-fn remove_paper_rolls(paper_rolls_grid: &mut Vec<Vec<PaperRollPlaceHolder>>) {
-    for paper_roll_line in paper_rolls_grid {
-        for paper_roll in paper_roll_line {
-            if paper_roll.is_removable() {
-                paper_roll.remove();
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum InputFormat {
+    // The diagram of the puzzle, `@` being a roll of paper.
+    Diagram,
+    // One `row,col` position of a roll of paper per line.
+    Coordinates,
+}
+
+struct Options {
+    rule: AccessibilityRule,
+    output_mode: OutputMode,
+    engine: Engine,
+    input_format: InputFormat,
+}
+
+// Supported arguments:
+//   --threshold N
+//   --neighbourhood moore[:RADIUS] | von-neumann[:RADIUS] | custom:DR,DC;DR,DC;...
+//   --wrap
+//   --depth-map
+//   --frames DIRECTORY [--format ppm|pgm] [--scale PIXELS_PER_CELL]
+//   --animate DELAY_MS
+//   --bitset
+//   --sparse
+//   --coordinates (implies --sparse)
+fn parse_options(args: &[String]) -> Options {
+    let default_rule = AccessibilityRule::default();
+    let mut neighbourhood = Neighbourhood::Moore(1);
+    let mut threshold = default_rule.threshold;
+    let mut boundary = default_rule.boundary;
+    let mut output_mode = OutputMode::Iterations;
+    let mut image_format = ImageFormat::Ppm;
+    let mut image_scale = 1;
+    let mut image_options_given = false;
+    let mut engine = Engine::Grid;
+    let mut input_format = InputFormat::Diagram;
+
+    let mut args_iter = args.iter();
+    while let Some(arg) = args_iter.next() {
+        match arg.as_str() {
+            "--threshold" => {
+                let Some(value) = args_iter.next() else {
+                    panic!("Missing value for `--threshold`");
+                };
+                let Ok(value) = value.parse() else {
+                    panic!("Failed to convert threshold {value}");
+                };
+                threshold = value;
+            }
+            "--neighbourhood" => {
+                let Some(value) = args_iter.next() else {
+                    panic!("Missing value for `--neighbourhood`");
+                };
+                neighbourhood = parse_neighbourhood(value);
+            }
+            "--wrap" => boundary = Boundary::Toroidal,
+            "--depth-map" => output_mode = OutputMode::DepthMap,
+            "--frames" => {
+                let Some(value) = args_iter.next() else {
+                    panic!("Missing value for `--frames`");
+                };
+                output_mode = OutputMode::Frames {
+                    directory: PathBuf::from(value),
+                    format: image_format,
+                    scale: image_scale,
+                };
+            }
+            "--format" => {
+                let Some(value) = args_iter.next() else {
+                    panic!("Missing value for `--format`");
+                };
+                image_format = match value.as_str() {
+                    "ppm" => ImageFormat::Ppm,
+                    "pgm" => ImageFormat::Pgm,
+                    _ => panic!("Unknown image format {value}"),
+                };
+                image_options_given = true;
+            }
+            "--scale" => {
+                let Some(value) = args_iter.next() else {
+                    panic!("Missing value for `--scale`");
+                };
+                let Ok(value) = value.parse() else {
+                    panic!("Failed to convert scale {value}");
+                };
+                image_scale = value;
+                image_options_given = true;
             }
+            "--animate" => {
+                let Some(value) = args_iter.next() else {
+                    panic!("Missing value for `--animate`");
+                };
+                let Ok(delay_ms) = value.parse() else {
+                    panic!("Failed to convert delay {value}");
+                };
+                output_mode = OutputMode::Animation(Duration::from_millis(delay_ms));
+            }
+            "--bitset" => engine = Engine::BitPacked,
+            "--sparse" => engine = Engine::Sparse,
+            "--coordinates" => {
+                engine = Engine::Sparse;
+                input_format = InputFormat::Coordinates;
+            }
+            _ => panic!("Unknown argument {arg}"),
         }
     }
-}
 
-fn main() {
-    let mut p1_total_paper_rolls_accessible: u32 = 0;
-    let mut p2_total_paper_rolls_removed: u32 = 0;
-    let mut paper_rolls_grid: Vec<Vec<PaperRollPlaceHolder>> = vec![];
+    // `--format` and `--scale` may be given before or after `--frames`.
+    if let OutputMode::Frames { format, scale, .. } = &mut output_mode {
+        *format = image_format;
+        *scale = image_scale;
+    } else if image_options_given {
+        panic!("`--format` and `--scale` only apply to `--frames DIRECTORY`");
+    }
 
-    let Ok(file) = std::fs::File::open("input.txt") else {
-        panic!("Failed to open file: `input.txt`");
+    let rule = AccessibilityRule::new(neighbourhood, threshold, boundary);
+    match engine {
+        Engine::Grid | Engine::BitPacked if input_format == InputFormat::Coordinates => {
+            panic!("Coordinates can only be read by the sparse map, drop `--bitset`");
+        }
+        Engine::Grid => {}
+        Engine::BitPacked => {
+            if rule != AccessibilityRule::default() {
+                panic!("The bit-packed map only supports the default accessibility rule");
+            }
+            if output_mode != OutputMode::Iterations {
+                panic!("The bit-packed map only reports the number of rolls removed per wave");
+            }
+        }
+        Engine::Sparse => {
+            if rule.boundary != Boundary::Walls {
+                panic!("The sparse map has no bounds to wrap around");
+            }
+            if output_mode != OutputMode::Iterations {
+                panic!("The sparse map only reports the number of rolls removed per wave");
+            }
+        }
+    }
+
+    Options {
+        rule,
+        output_mode,
+        engine,
+        input_format,
+    }
+}
+
+fn parse_neighbourhood(value: &str) -> Neighbourhood {
+    let (kind, parameter) = value.split_once(':').unwrap_or((value, "1"));
+    let parse_radius = |radius: &str| -> usize {
+        let Ok(radius) = radius.parse() else {
+            panic!("Failed to convert radius {radius}");
+        };
+        radius
     };
+    match kind {
+        "moore" => Neighbourhood::Moore(parse_radius(parameter)),
+        "von-neumann" => Neighbourhood::VonNeumann(parse_radius(parameter)),
+        "custom" => Neighbourhood::Custom(
+            parameter
+                .split(';')
+                .map(|offset| {
+                    let Some((dr, dc)) = offset.split_once(',') else {
+                        panic!("Wrong format for offset: {offset}");
+                    };
+                    let (Ok(dr), Ok(dc)) = (dr.trim().parse(), dc.trim().parse()) else {
+                        panic!("Failed to convert offset {offset}");
+                    };
+                    (dr, dc)
+                })
+                .collect(),
+        ),
+        _ => panic!("Unknown neighbourhood {kind}"),
+    }
+}
 
-    let reader = std::io::BufReader::new(file);
-    for line in reader.lines().map(|l| l.unwrap()) {
-        let paper_rolls_line: Vec<PaperRollPlaceHolder> = line
-            .chars()
-            .map(|c| PaperRollPlaceHolder::new(c == '@'))
-            .collect();
-        paper_rolls_grid.push(paper_rolls_line);
+// Waves 1 to 9 are shown as digits, then as letters. Rolls never removed are
+// shown as @.
+fn wave_symbol(wave: usize) -> char {
+    const SYMBOLS: &[u8] = b"123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+    SYMBOLS.get(wave - 1).map_or('+', |&c| c as char)
+}
+
+fn print_erosion_depth_map(
+    paper_rolls_grid: &Grid<PaperRollPlaceHolder>,
+    paper_rolls_removed_per_wave: &[usize],
+) {
+    let depth_map = paper_rolls_grid.map(|paper_roll| {
+        if paper_roll.has_paper_roll() {
+            '@'
+        } else {
+            paper_roll.removed_at_wave().map_or('.', wave_symbol)
+        }
+    });
+    println!("Erosion depth map:");
+    print!("{depth_map}");
+    println!();
+
+    println!("Removals per wave:");
+    let max_removed = paper_rolls_removed_per_wave
+        .iter()
+        .max()
+        .copied()
+        .unwrap_or(0);
+    for (idx, nb_removed) in paper_rolls_removed_per_wave.iter().enumerate() {
+        // Bars are scaled down so that the largest one is 50 characters long.
+        let bar_len = (nb_removed * 50).div_ceil(max_removed);
+        println!(
+            "Wave {:>3} ({}): {:>6} {}",
+            idx + 1,
+            wave_symbol(idx + 1),
+            nb_removed,
+            "#".repeat(bar_len)
+        );
     }
+    println!();
+
+    let stable_core = paper_rolls_grid.map(|paper_roll| {
+        if paper_roll.has_paper_roll() {
+            '@'
+        } else {
+            '.'
+        }
+    });
+    let nb_core_rolls = stable_core.iter().filter(|(_, c)| **c == '@').count();
+    println!("Stable core ({nb_core_rolls} rolls never removable):");
+    print!("{stable_core}");
+    println!();
+}
+
+fn run_grid_simulation(lines: &[String], options: Options) -> Vec<usize> {
+    let paper_rolls_grid = Grid::parse(lines, PaperRollPlaceHolder::new(false), |c| {
+        PaperRollPlaceHolder::new(c == '@')
+    });
+
+    let mut simulation = ForkliftSimulation::new(paper_rolls_grid, options.rule);
+    let mut paper_rolls_removed_per_wave: Vec<usize> = vec![];
+
+    let frame_exporter = match &options.output_mode {
+        OutputMode::Frames {
+            directory,
+            format,
+            scale,
+        } => Some(FrameExporter::new(directory, *format, *scale)),
+        _ => None,
+    };
 
     let mut iteration = 1;
-    println!("Iteration {}", iteration);
-    while can_remove_paper_rolls(&mut paper_rolls_grid, &mut p2_total_paper_rolls_removed) {
-        if iteration == 1 {
-            p1_total_paper_rolls_accessible = p2_total_paper_rolls_removed;
+    loop {
+        match &options.output_mode {
+            OutputMode::Iterations => {
+                println!("Iteration {}", iteration);
+                print!("{}", simulation.grid());
+            }
+            OutputMode::Animation(delay) => {
+                frames::print_ansi_frame(iteration, simulation.grid(), *delay);
+            }
+            OutputMode::DepthMap | OutputMode::Frames { .. } => {}
+        }
+        if let Some(frame_exporter) = &frame_exporter {
+            frame_exporter.write_frame(iteration, simulation.grid());
+        }
+        if simulation.accessible_paper_rolls().is_empty() {
+            break;
+        }
+        paper_rolls_removed_per_wave.push(simulation.remove_accessible_paper_rolls());
+        if options.output_mode == OutputMode::Iterations {
+            println!();
         }
-        remove_paper_rolls(&mut paper_rolls_grid);
-        println!();
         iteration += 1;
-        println!("Iteration {}", iteration);
     }
 
+    if options.output_mode == OutputMode::DepthMap {
+        print_erosion_depth_map(simulation.grid(), &paper_rolls_removed_per_wave);
+    }
+
+    paper_rolls_removed_per_wave
+}
+
+fn run_bit_packed_simulation(lines: &[String]) -> Vec<usize> {
+    let mut roll_map = BitRollMap::parse(lines);
+    println!(
+        "Bit-packed map of {}x{} with {} paper rolls",
+        roll_map.width(),
+        roll_map.height(),
+        roll_map.nb_paper_rolls()
+    );
+    roll_map.remove_paper_rolls_by_wave()
+}
+
+fn run_sparse_simulation(lines: &[String], options: Options) -> Vec<usize> {
+    let mut roll_map = match options.input_format {
+        InputFormat::Diagram => SparseRollMap::parse_diagram(lines),
+        InputFormat::Coordinates => SparseRollMap::parse_coordinates(lines),
+    };
+    println!("Sparse map with {} paper rolls", roll_map.nb_paper_rolls());
+    roll_map.remove_paper_rolls_by_wave(&options.rule)
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let options = parse_options(&args);
+
+    let Ok(file) = std::fs::File::open("input.txt") else {
+        panic!("Failed to open file: `input.txt`");
+    };
+
+    let reader = std::io::BufReader::new(file);
+    let lines: Vec<String> = reader.lines().map(|l| l.unwrap()).collect();
+
+    let paper_rolls_removed_per_wave = match options.engine {
+        Engine::Grid => run_grid_simulation(&lines, options),
+        Engine::BitPacked => run_bit_packed_simulation(&lines),
+        Engine::Sparse => run_sparse_simulation(&lines, options),
+    };
+
+    println!(
+        "Paper rolls removed per wave: {}",
+        paper_rolls_removed_per_wave
+            .iter()
+            .map(|n| n.to_string())
+            .collect::<Vec<String>>()
+            .join(", ")
+    );
+
+    let p1_total_paper_rolls_accessible = paper_rolls_removed_per_wave.first().unwrap_or(&0);
+    let p2_total_paper_rolls_removed: usize = paper_rolls_removed_per_wave.iter().sum();
+
     println!(
         "[PART 1] Total accessible paper rolls by the forklift: {}",
         p1_total_paper_rolls_accessible
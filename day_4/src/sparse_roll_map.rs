@@ -0,0 +1,121 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::AccessibilityRule;
+
+// Roll map only storing the positions of the rolls as (row, col), so that the
+// map has no bounds and its size only depends on the number of rolls.
+pub struct SparseRollMap {
+    paper_rolls: HashSet<(i64, i64)>,
+}
+
+impl SparseRollMap {
+    // Reads a diagram where each `@` is a roll of paper.
+    pub fn parse_diagram(lines: &[String]) -> Self {
+        let mut paper_rolls: HashSet<(i64, i64)> = HashSet::new();
+        for (row, line) in lines.iter().enumerate() {
+            for (col, c) in line.chars().enumerate() {
+                if c == '@' {
+                    paper_rolls.insert((row as i64, col as i64));
+                }
+            }
+        }
+        Self { paper_rolls }
+    }
+
+    // Reads one `row,col` position per line, coordinates can be negative.
+    pub fn parse_coordinates(lines: &[String]) -> Self {
+        let paper_rolls = lines
+            .iter()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                let Some((row, col)) = line.split_once(',') else {
+                    panic!("Wrong format for line: {line}");
+                };
+                let (Ok(row), Ok(col)) = (row.trim().parse(), col.trim().parse()) else {
+                    panic!("Failed to convert coordinates of line {line}");
+                };
+                (row, col)
+            })
+            .collect();
+        Self { paper_rolls }
+    }
+
+    pub fn nb_paper_rolls(&self) -> usize {
+        self.paper_rolls.len()
+    }
+
+    fn shifted_positions(
+        offsets: &[(isize, isize)],
+        (row, col): (i64, i64),
+    ) -> impl Iterator<Item = (i64, i64)> + '_ {
+        offsets
+            .iter()
+            .map(move |&(dr, dc)| (row + dr as i64, col + dc as i64))
+    }
+
+    fn neighbours(
+        rule: &AccessibilityRule,
+        pos: (i64, i64),
+    ) -> impl Iterator<Item = (i64, i64)> + '_ {
+        Self::shifted_positions(&rule.offsets, pos)
+    }
+
+    // Positions of the rolls counting `pos` among their neighbours.
+    fn dependents(
+        rule: &AccessibilityRule,
+        pos: (i64, i64),
+    ) -> impl Iterator<Item = (i64, i64)> + '_ {
+        Self::shifted_positions(&rule.reverse_offsets, pos)
+    }
+
+    // Same waves as `ForkliftSimulation`: the number of adjacent rolls is kept
+    // for each roll and only the rolls counting the removed ones are examined
+    // to build the next wave. Returns the number of rolls removed per wave.
+    pub fn remove_paper_rolls_by_wave(&mut self, rule: &AccessibilityRule) -> Vec<usize> {
+        let mut nb_adjacent_rolls: HashMap<(i64, i64), usize> = HashMap::new();
+        let mut accessible_paper_rolls: Vec<(i64, i64)> = vec![];
+        let mut removable: HashSet<(i64, i64)> = HashSet::new();
+
+        for &pos in &self.paper_rolls {
+            let count = Self::neighbours(rule, pos)
+                .filter(|neighbour| self.paper_rolls.contains(neighbour))
+                .count();
+            nb_adjacent_rolls.insert(pos, count);
+            if count < rule.threshold {
+                accessible_paper_rolls.push(pos);
+                removable.insert(pos);
+            }
+        }
+
+        let mut paper_rolls_removed_per_wave: Vec<usize> = vec![];
+        while !accessible_paper_rolls.is_empty() {
+            let wave = std::mem::take(&mut accessible_paper_rolls);
+
+            for pos in &wave {
+                self.paper_rolls.remove(pos);
+            }
+
+            for &pos in &wave {
+                for neighbour in Self::dependents(rule, pos) {
+                    if !self.paper_rolls.contains(&neighbour) {
+                        continue;
+                    }
+                    let Some(count) = nb_adjacent_rolls.get_mut(&neighbour) else {
+                        panic!(
+                            "No adjacent rolls count for ({}, {})",
+                            neighbour.0, neighbour.1
+                        );
+                    };
+                    *count -= 1;
+                    if *count < rule.threshold && removable.insert(neighbour) {
+                        accessible_paper_rolls.push(neighbour);
+                    }
+                }
+            }
+
+            paper_rolls_removed_per_wave.push(wave.len());
+        }
+
+        paper_rolls_removed_per_wave
+    }
+}
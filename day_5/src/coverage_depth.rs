@@ -0,0 +1,65 @@
+use crate::interval_set::IntervalSet;
+
+// Number of ranges covering each ID, as a step function: a sorted list of
+// disjoint intervals with the number of ranges covering each of their IDs.
+// IDs covered by no range are not listed.
+pub struct CoverageDepth {
+    steps: Vec<(u64, u64, usize)>,
+}
+
+impl CoverageDepth {
+    // Sweeps the bounds of the ranges in order: the depth goes up at the lower
+    // bound of a range and down right after its upper bound. Positions are
+    // `u128` so that a range ending at `u64::MAX` can be closed.
+    pub fn new(ranges: &[(u64, u64)]) -> Self {
+        let mut events: Vec<(u128, i64)> = Vec::with_capacity(ranges.len() * 2);
+        for &(lower, upper) in ranges {
+            events.push((lower as u128, 1));
+            events.push((upper as u128 + 1, -1));
+        }
+        events.sort();
+
+        let mut steps: Vec<(u64, u64, usize)> = vec![];
+        let mut depth: i64 = 0;
+        let mut previous_position: u128 = 0;
+        for (position, delta) in events {
+            if position > previous_position && depth > 0 {
+                let lower = previous_position as u64;
+                let upper = (position - 1) as u64;
+                match steps.last_mut() {
+                    Some((_, last_upper, last_depth))
+                        if *last_depth == depth as usize && *last_upper + 1 == lower =>
+                    {
+                        *last_upper = upper;
+                    }
+                    _ => steps.push((lower, upper, depth as usize)),
+                }
+            }
+            depth += delta;
+            previous_position = position;
+        }
+
+        Self { steps }
+    }
+
+    pub fn steps(&self) -> &[(u64, u64, usize)] {
+        &self.steps
+    }
+
+    pub fn max_depth(&self) -> usize {
+        self.steps
+            .iter()
+            .map(|&(_, _, depth)| depth)
+            .max()
+            .unwrap_or(0)
+    }
+
+    // IDs covered by at least `k` ranges.
+    pub fn covered_at_least(&self, k: usize) -> IntervalSet<u64> {
+        self.steps
+            .iter()
+            .filter(|&&(_, _, depth)| depth >= k)
+            .map(|&(lower, upper, _)| (lower, upper))
+            .collect()
+    }
+}
@@ -0,0 +1,180 @@
+use std::fmt::Display;
+
+// Integer types usable as bounds of inclusive intervals.
+pub trait IntervalBound: Copy + Ord + Display {
+    fn successor(self) -> Option<Self>;
+    fn predecessor(self) -> Option<Self>;
+    // Number of values in the inclusive interval `lower..=upper`.
+    fn span(lower: Self, upper: Self) -> u128;
+}
+
+macro_rules! impl_interval_bound {
+    ($($t:ty),*) => {
+        $(
+            impl IntervalBound for $t {
+                fn successor(self) -> Option<Self> {
+                    self.checked_add(1)
+                }
+
+                fn predecessor(self) -> Option<Self> {
+                    self.checked_sub(1)
+                }
+
+                fn span(lower: Self, upper: Self) -> u128 {
+                    (upper as i128 - lower as i128 + 1) as u128
+                }
+            }
+        )*
+    };
+}
+
+impl_interval_bound!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize);
+
+// Set of values stored as inclusive intervals. The intervals are kept sorted
+// and normalised: they never overlap nor touch each other, so `3-5` and `6-8`
+// are stored as `3-8`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IntervalSet<T> {
+    intervals: Vec<(T, T)>,
+}
+
+impl<T: IntervalBound> IntervalSet<T> {
+    pub fn new() -> Self {
+        Self { intervals: vec![] }
+    }
+
+    pub fn insert(&mut self, lower: T, upper: T) {
+        if lower > upper {
+            panic!("Invalid interval {lower}-{upper}");
+        }
+
+        // First interval ending right before `lower` or after it.
+        let first = self
+            .intervals
+            .partition_point(|&(_, up)| up.successor().is_some_and(|next| next < lower));
+        // First interval starting after `upper + 1`.
+        let last = match upper.successor() {
+            Some(next) => self.intervals.partition_point(|&(lw, _)| lw <= next),
+            None => self.intervals.len(),
+        };
+
+        let mut merged = (lower, upper);
+        if first < last {
+            merged.0 = merged.0.min(self.intervals[first].0);
+            merged.1 = merged.1.max(self.intervals[last - 1].1);
+        }
+        self.intervals.splice(first..last, [merged]);
+    }
+
+    // Binary search of the last interval starting at or before `value`.
+    pub fn contains(&self, value: T) -> bool {
+        let idx = self.intervals.partition_point(|&(lw, _)| lw <= value);
+        idx > 0 && value <= self.intervals[idx - 1].1
+    }
+
+    // Number of values covered by the set.
+    pub fn len_covered(&self) -> u128 {
+        self.intervals
+            .iter()
+            .map(|&(lower, upper)| T::span(lower, upper))
+            .sum()
+    }
+
+    // Number of disjoint intervals.
+    pub fn len(&self) -> usize {
+        self.intervals.len()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (T, T)> + '_ {
+        self.intervals.iter().copied()
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        self.iter().chain(other.iter()).collect()
+    }
+
+    // Both sets being sorted, walk them side by side and keep the overlap of
+    // the current intervals, then move past the one ending first.
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut intervals: Vec<(T, T)> = vec![];
+        let (mut i, mut j) = (0, 0);
+        while i < self.intervals.len() && j < other.intervals.len() {
+            let (lw_a, up_a) = self.intervals[i];
+            let (lw_b, up_b) = other.intervals[j];
+            let lower = lw_a.max(lw_b);
+            let upper = up_a.min(up_b);
+            if lower <= upper {
+                intervals.push((lower, upper));
+            }
+            if up_a < up_b {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        // Overlaps of disjoint, non touching intervals can't touch either.
+        Self { intervals }
+    }
+
+    // Values of `lower..=upper` not in the set.
+    pub fn complement(&self, lower: T, upper: T) -> Self {
+        if lower > upper {
+            panic!("Invalid bounding interval {lower}-{upper}");
+        }
+
+        let mut intervals: Vec<(T, T)> = vec![];
+        // Next value not yet known to be covered, `None` once past `upper`.
+        let mut next = Some(lower);
+        for (lw, up) in self.iter() {
+            let Some(start) = next else {
+                break;
+            };
+            if up < start {
+                continue;
+            }
+            if lw > upper {
+                break;
+            }
+            if lw > start {
+                // `lw > start >= T::MIN` so `lw` has a predecessor.
+                intervals.push((start, lw.predecessor().unwrap()));
+            }
+            next = up.successor().filter(|&n| n <= upper);
+        }
+        if let Some(start) = next {
+            intervals.push((start, upper));
+        }
+        Self { intervals }
+    }
+
+    // Values of the set not in `other`.
+    pub fn difference(&self, other: &Self) -> Self {
+        let (Some(&(lower, _)), Some(&(_, upper))) =
+            (self.intervals.first(), self.intervals.last())
+        else {
+            return Self::new();
+        };
+        self.intersection(&other.complement(lower, upper))
+    }
+
+    // Values in exactly one of the two sets.
+    pub fn symmetric_difference(&self, other: &Self) -> Self {
+        self.difference(other).union(&other.difference(self))
+    }
+}
+
+impl<T: IntervalBound> Default for IntervalSet<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: IntervalBound> FromIterator<(T, T)> for IntervalSet<T> {
+    fn from_iter<I: IntoIterator<Item = (T, T)>>(iter: I) -> Self {
+        let mut set = Self::new();
+        for (lower, upper) in iter {
+            set.insert(lower, upper);
+        }
+        set
+    }
+}
@@ -79,87 +79,351 @@
 // Process the database file again. How many ingredient IDs are considered to be
 // fresh according to the fresh ingredient ID ranges?
 
-use std::io::BufRead;
+mod coverage_depth;
+mod interval_set;
 
-fn main() {
-    let mut p1_total_of_fresh_ingredient = 0;
-    let mut p2_total_of_fresh_ingredient = 0;
+use std::{
+    collections::BTreeSet,
+    io::{BufRead, Write},
+};
+
+use coverage_depth::CoverageDepth;
+use interval_set::IntervalSet;
+
+struct Database {
+    // Ranges as listed in the database.
+    original_ranges: Vec<(u64, u64)>,
+    fresh_ingredient_ranges: IntervalSet<u64>,
+    available_ingredients: Vec<u64>,
+}
+
+fn parse_range(range: &str) -> (u64, u64) {
+    let Some((lower_range, upper_range)) = range.split_once("-") else {
+        panic!("Failed to split range of line {}", range);
+    };
+    let Ok(lower_range) = lower_range.parse() else {
+        panic!("Failed to convert lower range {}", lower_range);
+    };
+    let Ok(upper_range) = upper_range.parse() else {
+        panic!("Failed to convert upper range {}", upper_range);
+    };
+    (lower_range, upper_range)
+}
+
+fn read_database(path: &str) -> Database {
     let mut reading_fresh_ingredient_ranges: bool = true;
-    let mut fresh_ingredient_ranges: Vec<(u64, u64)> = vec![];
+    let mut database = Database {
+        original_ranges: vec![],
+        fresh_ingredient_ranges: IntervalSet::new(),
+        available_ingredients: vec![],
+    };
 
-    let Ok(file) = std::fs::File::open("input.txt") else {
-        panic!("Failed to open file: `input.txt`");
+    let Ok(file) = std::fs::File::open(path) else {
+        panic!("Failed to open file: `{path}`");
     };
 
     let reader = std::io::BufReader::new(file);
     for line in reader.lines().map(|l| l.unwrap()) {
         if line.is_empty() {
-            println!(
-                "This is the blank line separating fresh ingredient ID ranges from the list of available ingredient IDs."
-            );
             reading_fresh_ingredient_ranges = false;
             continue;
         }
         if reading_fresh_ingredient_ranges {
-            let Some((lower_range, upper_range)) = line.split_once("-") else {
-                panic!("Failed to split range of line {}", line);
-            };
-            let Ok(lower_range) = lower_range.parse() else {
-                panic!("Failed to convert lower range {}", lower_range);
-            };
-            let Ok(upper_range) = upper_range.parse() else {
-                panic!("Failed to convert upper range {}", upper_range);
-            };
-            fresh_ingredient_ranges.push((lower_range, upper_range));
+            let (lower_range, upper_range) = parse_range(&line);
+            database.original_ranges.push((lower_range, upper_range));
+            database
+                .fresh_ingredient_ranges
+                .insert(lower_range, upper_range);
         } else {
             let Ok(value) = line.parse::<u64>() else {
                 panic!("Failed to convert ingredient for line {line}");
             };
-            let mut is_fresh: bool = false;
-            for (lower_range, upper_range) in &fresh_ingredient_ranges {
-                if value >= *lower_range && value <= *upper_range {
-                    println!(
-                        "Ingredient ID {value} is fresh, falls into range {lower_range}-{upper_range}"
-                    );
-                    is_fresh = true;
-                    break;
-                }
-            }
-            if is_fresh {
-                p1_total_of_fresh_ingredient += 1;
-            } else {
-                println!("Ingredient ID {value} is spoiled!");
+            database.available_ingredients.push(value);
+        }
+    }
+
+    database
+}
+
+struct FreshnessQuery {
+    ingredient_id: u64,
+    is_fresh: bool,
+    // Original ranges containing the ID, in database order.
+    containing_ranges: Vec<(u64, u64)>,
+}
+
+// Answers the freshness of each ID with a binary search in the merged ranges.
+// To also find all the original ranges containing each ID, the IDs are sorted
+// and swept along the original ranges sorted by lower bound, keeping the set
+// of ranges open at the current ID ordered by upper bound.
+fn query_freshness(database: &Database, ingredient_ids: &[u64]) -> Vec<FreshnessQuery> {
+    let mut ranges_by_lower: Vec<usize> = (0..database.original_ranges.len()).collect();
+    ranges_by_lower.sort_by_key(|&idx| database.original_ranges[idx].0);
+    let mut ids_order: Vec<usize> = (0..ingredient_ids.len()).collect();
+    ids_order.sort_by_key(|&idx| ingredient_ids[idx]);
+
+    let mut queries: Vec<FreshnessQuery> = ingredient_ids
+        .iter()
+        .map(|&ingredient_id| FreshnessQuery {
+            ingredient_id,
+            is_fresh: database.fresh_ingredient_ranges.contains(ingredient_id),
+            containing_ranges: vec![],
+        })
+        .collect();
+
+    let mut open_ranges: BTreeSet<(u64, usize)> = BTreeSet::new();
+    let mut next_range = 0;
+    for query_idx in ids_order {
+        let ingredient_id = ingredient_ids[query_idx];
+        while next_range < ranges_by_lower.len()
+            && database.original_ranges[ranges_by_lower[next_range]].0 <= ingredient_id
+        {
+            let range_idx = ranges_by_lower[next_range];
+            open_ranges.insert((database.original_ranges[range_idx].1, range_idx));
+            next_range += 1;
+        }
+        while let Some(&(upper, _)) = open_ranges.first()
+            && upper < ingredient_id
+        {
+            open_ranges.pop_first();
+        }
+
+        let mut containing: Vec<usize> = open_ranges.iter().map(|&(_, idx)| idx).collect();
+        containing.sort();
+        queries[query_idx].containing_ranges = containing
+            .into_iter()
+            .map(|idx| database.original_ranges[idx])
+            .collect();
+    }
+
+    queries
+}
+
+fn print_ranges(title: &str, ranges: &IntervalSet<u64>) {
+    println!(
+        "{title}: {} IDs in {} ranges",
+        ranges.len_covered(),
+        ranges.len()
+    );
+    ranges.iter().for_each(|(lw, up)| println!("{lw}-{up}"));
+}
+
+// Reports the IDs that are fresh according to one database but not the other.
+fn compare_databases(path: &str, other_path: &str) {
+    let fresh = read_database(path).fresh_ingredient_ranges;
+    let other_fresh = read_database(other_path).fresh_ingredient_ranges;
+
+    print_ranges(
+        &format!("Fresh in `{path}` but not in `{other_path}`"),
+        &fresh.difference(&other_fresh),
+    );
+    print_ranges(
+        &format!("Fresh in `{other_path}` but not in `{path}`"),
+        &other_fresh.difference(&fresh),
+    );
+    print_ranges("Fresh in both databases", &fresh.intersection(&other_fresh));
+    print_ranges(
+        "Fresh in only one database",
+        &fresh.symmetric_difference(&other_fresh),
+    );
+    print_ranges("Fresh in any database", &fresh.union(&other_fresh));
+}
+
+// Reports the IDs within `bounds` that no range of the database considers
+// fresh.
+fn print_spoiled_ranges(path: &str, bounds: &str) {
+    let (lower, upper) = parse_range(bounds);
+    let fresh = read_database(path).fresh_ingredient_ranges;
+    print_ranges(
+        &format!("Spoiled within {lower}-{upper}"),
+        &fresh.complement(lower, upper),
+    );
+}
+
+// Reports how many ranges of the database cover each ID, where the most
+// overlapped IDs are and which IDs are covered by at least `k` ranges.
+fn print_coverage_depth(path: &str, k: usize) {
+    let database = read_database(path);
+    let coverage_depth = CoverageDepth::new(&database.original_ranges);
+
+    println!("Coverage depth:");
+    for &(lower, upper, depth) in coverage_depth.steps() {
+        println!("{lower}-{upper}: {depth}");
+    }
+
+    let max_depth = coverage_depth.max_depth();
+    print_ranges(
+        &format!("Covered by the maximum of {max_depth} ranges"),
+        &coverage_depth.covered_at_least(max_depth),
+    );
+    print_ranges(
+        &format!("Covered by at least {k} ranges"),
+        &coverage_depth.covered_at_least(k),
+    );
+}
+
+// Loads the fresh ranges once, then answers each ingredient ID read on stdin
+// as soon as its line is received, so the database can be used as a filter.
+fn stream_queries(path: &str) {
+    let fresh_ingredient_ranges = read_database(path).fresh_ingredient_ranges;
+
+    let stdin = std::io::stdin().lock();
+    let mut stdout = std::io::stdout().lock();
+    for line in stdin.lines() {
+        let Ok(line) = line else {
+            panic!("Failed to read ingredient ID from stdin");
+        };
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let answer = match line.parse::<u64>() {
+            Ok(value) if fresh_ingredient_ranges.contains(value) => "fresh",
+            Ok(_) => "spoiled",
+            Err(_) => {
+                eprintln!("Failed to convert ingredient for line {line}");
+                "invalid"
             }
+        };
+        // Stop quietly when the reading end of the pipe is closed.
+        if writeln!(stdout, "{line} {answer}").is_err() || stdout.flush().is_err() {
+            break;
         }
     }
+}
 
-    fresh_ingredient_ranges.sort_by(|a, b| a.0.cmp(&b.0));
-    let mut merged_fresh_ingredient_ranges: Vec<(u64, u64)> = vec![];
-    for (lower_range, upper_range) in fresh_ingredient_ranges {
-        let mut merged: bool = false;
-        for (lw_range, up_range) in &mut merged_fresh_ingredient_ranges {
-            if *lw_range >= lower_range && *lw_range <= upper_range {
-                if *up_range <= upper_range {
-                    *up_range = upper_range;
-                }
-                *lw_range = lower_range;
-                merged = true;
-            } else if *lw_range <= lower_range && *up_range >= lower_range {
-                if *up_range <= upper_range {
-                    *up_range = upper_range;
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ExportFormat {
+    // Same `lo-hi` format as the input, followed by the available IDs.
+    Database,
+    Json,
+}
+
+fn write_database(
+    database: &Database,
+    format: ExportFormat,
+    writer: &mut impl Write,
+) -> std::io::Result<()> {
+    match format {
+        ExportFormat::Database => {
+            for (lw, up) in database.fresh_ingredient_ranges.iter() {
+                writeln!(writer, "{lw}-{up}")?;
+            }
+            if !database.available_ingredients.is_empty() {
+                writeln!(writer)?;
+                for value in &database.available_ingredients {
+                    writeln!(writer, "{value}")?;
                 }
-                merged = true;
             }
         }
-        if !merged {
-            merged_fresh_ingredient_ranges.push((lower_range, upper_range));
+        ExportFormat::Json => {
+            let ranges: Vec<String> = database
+                .fresh_ingredient_ranges
+                .iter()
+                .map(|(lw, up)| format!("{{\"lower\": {lw}, \"upper\": {up}}}"))
+                .collect();
+            let ingredients: Vec<String> = database
+                .available_ingredients
+                .iter()
+                .map(|value| value.to_string())
+                .collect();
+            writeln!(writer, "{{")?;
+            writeln!(
+                writer,
+                "  \"fresh_ingredient_ranges\": [{}],",
+                ranges.join(", ")
+            )?;
+            writeln!(
+                writer,
+                "  \"available_ingredients\": [{}]",
+                ingredients.join(", ")
+            )?;
+            writeln!(writer, "}}")?;
+        }
+    }
+    writer.flush()
+}
+
+// Writes the database with its ranges merged, to stdout if `output` is `-`.
+fn export_database(path: &str, output: &str, format: ExportFormat) {
+    let database = read_database(path);
+    let result = if output == "-" {
+        write_database(&database, format, &mut std::io::stdout().lock())
+    } else {
+        let Ok(file) = std::fs::File::create(output) else {
+            panic!("Failed to create file: `{output}`");
+        };
+        write_database(&database, format, &mut std::io::BufWriter::new(file))
+    };
+    if result.is_err() {
+        panic!("Failed to export database to `{output}`");
+    }
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args
+        .iter()
+        .map(|a| a.as_str())
+        .collect::<Vec<&str>>()
+        .as_slice()
+    {
+        [] => {}
+        ["--compare", other_path] => return compare_databases("input.txt", other_path),
+        ["--compare", path, other_path] => return compare_databases(path, other_path),
+        ["--spoiled", bounds] => return print_spoiled_ranges("input.txt", bounds),
+        ["--stream"] => return stream_queries("input.txt"),
+        ["--stream", path] => return stream_queries(path),
+        ["--export", output] => {
+            return export_database("input.txt", output, ExportFormat::Database);
+        }
+        ["--export-json", output] => {
+            return export_database("input.txt", output, ExportFormat::Json);
+        }
+        ["--depth"] => return print_coverage_depth("input.txt", 2),
+        ["--depth", k] => {
+            let Ok(k) = k.parse() else {
+                panic!("Failed to convert depth {k}");
+            };
+            return print_coverage_depth("input.txt", k);
         }
+        _ => panic!(
+            "Usage: day_5 [--compare [DATABASE] OTHER_DATABASE | --spoiled LOWER-UPPER | --depth [K] | --stream [DATABASE] | --export OUTPUT | --export-json OUTPUT]"
+        ),
     }
 
-    merged_fresh_ingredient_ranges.iter().for_each(|(lw, up)| {
-        println!("Merged range {lw}-{up}");
-        p2_total_of_fresh_ingredient += up - lw + 1;
-    });
+    let mut p1_total_of_fresh_ingredient = 0;
+    let database = read_database("input.txt");
+
+    for query in query_freshness(&database, &database.available_ingredients) {
+        let value = query.ingredient_id;
+        if query.is_fresh {
+            let ranges: Vec<String> = query
+                .containing_ranges
+                .iter()
+                .map(|(lw, up)| format!("range {lw}-{up}"))
+                .collect();
+            println!(
+                "Ingredient ID {value} is fresh, falls into {}",
+                ranges.join(" as well as ")
+            );
+            p1_total_of_fresh_ingredient += 1;
+        } else {
+            println!("Ingredient ID {value} is spoiled!");
+        }
+    }
+
+    let fresh_ingredient_ranges = database.fresh_ingredient_ranges;
+
+    println!(
+        "{} merged fresh ingredient ranges",
+        fresh_ingredient_ranges.len()
+    );
+    fresh_ingredient_ranges
+        .iter()
+        .for_each(|(lw, up)| println!("Merged range {lw}-{up}"));
+    let p2_total_of_fresh_ingredient = fresh_ingredient_ranges.len_covered();
 
     println!(
         "[PART 1] Total of fresh ingredient: {}",
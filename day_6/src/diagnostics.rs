@@ -0,0 +1,155 @@
+use std::fmt::Display;
+use std::ops::Range;
+
+use common::Grid;
+
+use crate::worksheet::{Operator, OperatorPosition, problem_blocks};
+
+// Layout issues of a worksheet, positions are 0-based (row, column) of the
+// worksheet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Diagnostic {
+    // The row is shorter than the widest one and gets padded with spaces.
+    RaggedRow {
+        row: usize,
+        length: usize,
+        width: usize,
+    },
+    // The operator is not under the first column of its problem.
+    MisalignedOperator {
+        column: usize,
+        problem: Range<usize>,
+    },
+    MissingOperator {
+        problem: Range<usize>,
+    },
+    // Another operator than the first one found below the problem.
+    ExtraOperator {
+        column: usize,
+        problem: Range<usize>,
+    },
+    UnknownOperator {
+        column: usize,
+        symbol: char,
+    },
+    // Anything else than a digit or a space outside of the operator row.
+    InvalidCharacter {
+        row: usize,
+        column: usize,
+        symbol: char,
+    },
+}
+
+impl Diagnostic {
+    // Whether the worksheet can't be read at all. Other issues are only
+    // suspicious, the worksheet still reads unambiguously.
+    pub fn is_fatal(&self) -> bool {
+        !matches!(
+            self,
+            Diagnostic::RaggedRow { .. } | Diagnostic::MisalignedOperator { .. }
+        )
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Diagnostic::RaggedRow { row, length, width } => write!(
+                f,
+                "Row {row} has {length} characters instead of {width}, padded with spaces"
+            ),
+            Diagnostic::MisalignedOperator { column, problem } => write!(
+                f,
+                "Operator at column {column} is not under the first column of problem at columns {}-{}",
+                problem.start,
+                problem.end - 1
+            ),
+            Diagnostic::MissingOperator { problem } => write!(
+                f,
+                "Problem at columns {}-{} has no operator",
+                problem.start,
+                problem.end - 1
+            ),
+            Diagnostic::ExtraOperator { column, problem } => write!(
+                f,
+                "Extra operator at column {column} for problem at columns {}-{}",
+                problem.start,
+                problem.end - 1
+            ),
+            Diagnostic::UnknownOperator { column, symbol } => {
+                write!(f, "Unknown operator {symbol:?} at column {column}")
+            }
+            Diagnostic::InvalidCharacter {
+                row,
+                column,
+                symbol,
+            } => write!(
+                f,
+                "Invalid character {symbol:?} at row {row}, column {column}"
+            ),
+        }
+    }
+}
+
+// Checks the layout of the worksheet before it gets split into problems, an
+// empty list meaning it is well-formed.
+pub fn diagnose(lines: &[String], operator_position: OperatorPosition) -> Vec<Diagnostic> {
+    let mut diagnostics: Vec<Diagnostic> = vec![];
+    let grid = Grid::parse(lines, ' ', |c| c);
+    let Some(operator_row) = operator_position.operator_row(grid.height()) else {
+        return diagnostics;
+    };
+
+    for (row, line) in lines.iter().enumerate() {
+        let length = line.chars().count();
+        if length < grid.width() {
+            diagnostics.push(Diagnostic::RaggedRow {
+                row,
+                length,
+                width: grid.width(),
+            });
+        }
+    }
+
+    for ((row, column), &symbol) in grid.iter() {
+        if row == operator_row {
+            if symbol != ' ' && Operator::from_char(symbol).is_none() {
+                diagnostics.push(Diagnostic::UnknownOperator { column, symbol });
+            }
+        } else if symbol != ' ' && !symbol.is_ascii_digit() {
+            diagnostics.push(Diagnostic::InvalidCharacter {
+                row,
+                column,
+                symbol,
+            });
+        }
+    }
+
+    for problem in problem_blocks(&grid) {
+        let operator_columns: Vec<usize> = problem
+            .clone()
+            .filter(|&column| grid[(operator_row, column)] != ' ')
+            .collect();
+        match operator_columns.as_slice() {
+            [] => diagnostics.push(Diagnostic::MissingOperator {
+                problem: problem.clone(),
+            }),
+            [first, others @ ..] => {
+                if *first != problem.start {
+                    diagnostics.push(Diagnostic::MisalignedOperator {
+                        column: *first,
+                        problem: problem.clone(),
+                    });
+                }
+                for &column in others {
+                    diagnostics.push(Diagnostic::ExtraOperator {
+                        column,
+                        problem: problem.clone(),
+                    });
+                }
+            }
+        }
+    }
+
+    diagnostics
+}
@@ -82,122 +82,118 @@
 // Solve the problems on the math worksheet again. What is the grand total found
 // by adding together all of the answers to the individual problems?
 
+mod diagnostics;
+mod pretty_printer;
+mod worksheet;
+
 use std::io::BufRead;
 
-fn part1_solver(lines: &[String]) {
-    let grid_problems: Vec<Vec<String>> = lines
-        .iter()
-        .map(|line| line.split_whitespace().map(|s| s.to_string()).collect())
-        .collect();
-    let rows = grid_problems.len();
-    let mut res_problems: Vec<u64> = grid_problems[0]
-        .iter()
-        .map(|s| {
-            s.parse::<u64>()
-                .expect(&format!("Failed to convert first row {s}!"))
-        })
-        .collect();
-    for row in 1..(rows - 1) {
-        let cols = grid_problems[row].len();
-        for col in 0..cols {
-            let operator = &grid_problems[rows - 1][col];
-            let value = &grid_problems[row][col];
-            let Ok(val) = value.parse::<u64>() else {
-                panic!("Failed to convert value {value} at row: {row}, col:{col}");
-            };
-            match operator.as_str() {
-                "+" => res_problems[col] += val,
-                "*" => res_problems[col] *= val,
-                _ => panic!("Unknown operator {}", operator),
-            };
-        }
+use common::BigUint;
+use diagnostics::diagnose;
+use pretty_printer::print_problem;
+use worksheet::{
+    DigitOrder, EvaluationError, OperatorPosition, Problem, ReadingMode, ReadingOrder, Worksheet,
+    WorksheetNumber,
+};
+
+fn evaluate<T: WorksheetNumber>(problem: &Problem, mode: ReadingMode) -> T {
+    match problem.evaluate(mode) {
+        Ok(result) => result,
+        Err(error) => panic!(
+            "Problem at columns {}-{}: {error}{}",
+            problem.columns.start,
+            problem.columns.end - 1,
+            overflow_hint::<T>(&error)
+        ),
     }
+}
 
-    let p1_total_of_individual_problems: u64 = res_problems.iter().sum();
-
-    println!("[PART 1] Grand total of individual problems {p1_total_of_individual_problems}");
+fn overflow_hint<T: WorksheetNumber>(error: &EvaluationError) -> &'static str {
+    if *error == EvaluationError::Overflow && T::IS_BOUNDED {
+        " (run with `--big` for exact results)"
+    } else {
+        ""
+    }
 }
 
-fn part2_solver(lines: &[String]) {
-    let rows = lines.len();
-    let Some(max_col) = lines.iter().map(|l| l.len()).max() else {
-        panic!("Failed to find max column number of all problems!");
+fn add_to_grand_total<T: WorksheetNumber>(grand_total: T, result: &T) -> T {
+    let Some(grand_total) = grand_total.checked_add(result) else {
+        panic!(
+            "Grand total overflows{}",
+            overflow_hint::<T>(&EvaluationError::Overflow)
+        );
     };
+    grand_total
+}
 
-    let mut problems: Vec<Vec<u64>> = vec![];
-    let mut operators: Vec<String> = vec![];
-    let mut current_problem: Vec<u64> = vec![];
-
-    for col in (0..max_col).rev() {
-        let mut column_chars: Vec<char> = vec![];
-        let mut all_spaces = true;
-
-        for row in 0..rows {
-            let c = lines[row].chars().nth(col).unwrap_or(' ');
-            column_chars.push(c);
-            if c != ' ' {
-                all_spaces = false;
-            }
-        }
-
-        if all_spaces {
-            if !current_problem.is_empty() {
-                problems.push(current_problem.clone());
-                current_problem.clear();
-            }
-            continue;
+fn part1_solver<T: WorksheetNumber>(worksheet: &Worksheet, zero: T, show: bool) {
+    let mut p1_total_of_individual_problems = zero;
+    for (idx, problem) in worksheet.problems.iter().enumerate() {
+        let result = evaluate::<T>(problem, ReadingMode::ROW_WISE);
+        if show {
+            let title = format!("[PART 1] Problem {}", idx + 1);
+            print_problem(&title, problem, ReadingMode::ROW_WISE, &result);
         }
+        p1_total_of_individual_problems =
+            add_to_grand_total(p1_total_of_individual_problems, &result);
+    }
 
-        let mut number = String::new();
-        for row in 0..(rows - 1) {
-            let c = column_chars[row];
-            if c != ' ' {
-                number.push(c);
-            }
-        }
+    println!("[PART 1] Grand total of individual problems {p1_total_of_individual_problems}");
+}
 
-        if !number.is_empty() {
-            if let Ok(n) = number.parse::<u64>() {
-                current_problem.push(n);
-            }
-        }
-        let operator = column_chars[rows - 1].to_string();
-        if operator == "+" || operator == "*" {
-            operators.push(operator);
+fn part2_solver<T: WorksheetNumber>(worksheet: &Worksheet, mode: ReadingMode, zero: T, show: bool) {
+    let mut grand_total = zero;
+    // Problems are numbered in the direction the columns are read.
+    let problems: Vec<&Problem> = match mode.order {
+        ReadingOrder::RightToLeftColumns => worksheet.problems.iter().rev().collect(),
+        _ => worksheet.problems.iter().collect(),
+    };
+    for (idx, problem) in problems.into_iter().enumerate() {
+        let result = evaluate::<T>(problem, mode);
+        if show {
+            let title = format!("[PART 2] Problem {}", idx + 1);
+            print_problem(&title, problem, mode, &result);
+        } else {
+            println!("Problem {}: {}", idx + 1, result);
         }
+        grand_total = add_to_grand_total(grand_total, &result);
     }
 
-    if !current_problem.is_empty() {
-        problems.push(current_problem);
-    }
-
-    let mut grand_total: u64 = 0;
-    for (idx, problem) in problems.iter().enumerate() {
-        if problem.is_empty() || idx >= operators.len() {
-            continue;
-        }
+    println!("[PART 2] Grand total: {}", grand_total);
+}
 
-        let operator = &operators[idx];
-        let mut result = 1;
+const USAGE: &str = "Usage: day_6 [--big] [--show] [--order rows|right-to-left|left-to-right] \
+                     [--bottom-up] [--operator-top]\n\
+                     Operators: + * - / ^ % < (min) > (max)";
 
-        match operator.as_str() {
-            "+" => result = problem.iter().sum(),
-            "*" => {
-                for num in problem {
-                    result *= num;
+fn main() {
+    // `--big` evaluates the worksheet with arbitrary-precision integers,
+    // `--show` prints the equation read from each problem.
+    // `--order` and `--bottom-up` change how part 2 reads the problems, and
+    // `--operator-top` expects the operators above the problems.
+    let mut big = false;
+    let mut show = false;
+    let mut mode = ReadingMode::CEPHALOPOD;
+    let mut operator_position = OperatorPosition::Bottom;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--big" => big = true,
+            "--show" => show = true,
+            "--order" => {
+                mode.order = match args.next().as_deref() {
+                    Some("rows") => ReadingOrder::RowWise,
+                    Some("right-to-left") => ReadingOrder::RightToLeftColumns,
+                    Some("left-to-right") => ReadingOrder::LeftToRightColumns,
+                    _ => panic!("{USAGE}"),
                 }
             }
-            _ => panic!("Problem {idx}: Unknown operator {operator}"),
+            "--bottom-up" => mode.digits = DigitOrder::LeastSignificantFirst,
+            "--operator-top" => operator_position = OperatorPosition::Top,
+            _ => panic!("{USAGE}"),
         }
-
-        println!("Problem {}: {}", idx + 1, result);
-        grand_total += result;
     }
 
-    println!("[PART 2] Grand total: {}", grand_total);
-}
-
-fn main() {
     let Ok(file) = std::fs::File::open("input.txt") else {
         panic!("Failed to open file: `input.txt`");
     };
@@ -205,6 +201,22 @@ fn main() {
     let reader = std::io::BufReader::new(file);
     let lines: Vec<String> = reader.lines().map(|l| l.unwrap()).collect();
 
-    part1_solver(&lines);
-    part2_solver(&lines);
+    let diagnostics = diagnose(&lines, operator_position);
+    for diagnostic in diagnostics.iter() {
+        println!("[WARNING] {diagnostic}");
+    }
+    let nb_fatal = diagnostics.iter().filter(|d| d.is_fatal()).count();
+    if nb_fatal > 0 {
+        panic!("Malformed worksheet, {nb_fatal} issue(s) prevent reading it");
+    }
+
+    let worksheet = Worksheet::parse(&lines, operator_position);
+
+    if big {
+        part1_solver(&worksheet, BigUint::zero(), show);
+        part2_solver(&worksheet, mode, BigUint::zero(), show);
+    } else {
+        part1_solver(&worksheet, 0u64, show);
+        part2_solver(&worksheet, mode, 0u64, show);
+    }
 }
@@ -0,0 +1,65 @@
+use std::fmt::Display;
+
+use common::Grid;
+
+use crate::worksheet::{OperatorPosition, Problem, ReadingMode};
+
+// ANSI colours cycled through to tell the numbers of a problem apart.
+const NUMBER_COLOURS: [u8; 6] = [31, 32, 33, 34, 35, 36];
+
+fn highlight(text: &str, colour: u8) -> String {
+    format!("\x1b[1;{colour}m{text}\x1b[0m")
+}
+
+// Prints the cells of a problem, the digits of each number highlighted in their
+// own colour, and the equation they form next to the operator row.
+pub fn print_problem(title: &str, problem: &Problem, mode: ReadingMode, result: &impl Display) {
+    let cells = &problem.cells;
+    let mut colours: Grid<Option<u8>> = Grid::new(cells.width(), cells.height(), None);
+    let mut terms: Vec<String> = vec![];
+    for (idx, positions) in problem.digit_positions(mode).iter().enumerate() {
+        let colour = NUMBER_COLOURS[idx % NUMBER_COLOURS.len()];
+        for &pos in positions {
+            colours[pos] = Some(colour);
+        }
+        let digits: String = positions.iter().map(|&pos| cells[pos]).collect();
+        terms.push(highlight(&digits, colour));
+    }
+
+    println!(
+        "{title} (columns {}-{}):",
+        problem.columns.start,
+        problem.columns.end - 1
+    );
+    let digit_lines: Vec<String> = (0..cells.height())
+        .map(|row| {
+            (0..cells.width())
+                .map(|col| match colours[(row, col)] {
+                    Some(colour) => highlight(&cells[(row, col)].to_string(), colour),
+                    None => cells[(row, col)].to_string(),
+                })
+                .collect()
+        })
+        .collect();
+    let operator_line: String = (0..cells.width())
+        .map(|col| {
+            if col == problem.operator_column {
+                problem.operator.symbol()
+            } else {
+                ' '
+            }
+        })
+        .collect();
+    let symbol = format!(" {} ", problem.operator.symbol());
+    let operator_line = format!("{operator_line}   {} = {result}", terms.join(&symbol));
+
+    if problem.operator_position == OperatorPosition::Top {
+        println!("    {operator_line}");
+    }
+    for line in digit_lines {
+        println!("    {line}");
+    }
+    if problem.operator_position == OperatorPosition::Bottom {
+        println!("    {operator_line}");
+    }
+}
@@ -0,0 +1,423 @@
+use std::{fmt::Display, ops::Range, str::FromStr};
+
+use common::{BigUint, Grid};
+
+// Numbers a worksheet can be evaluated with, all operations report overflows
+// instead of wrapping around.
+pub trait WorksheetNumber: Clone + Ord + Display + FromStr {
+    // Whether additions and multiplications can overflow.
+    const IS_BOUNDED: bool;
+
+    fn is_zero(&self) -> bool;
+    fn checked_add(&self, other: &Self) -> Option<Self>;
+    fn checked_sub(&self, other: &Self) -> Option<Self>;
+    fn checked_mul(&self, other: &Self) -> Option<Self>;
+    // `None` when dividing by zero.
+    fn checked_div_rem(&self, other: &Self) -> Option<(Self, Self)>;
+    // Powers of 0 and 1 never fail, whatever the exponent.
+    fn checked_pow(&self, exponent: &Self) -> Result<Self, EvaluationError>;
+}
+
+impl WorksheetNumber for u64 {
+    const IS_BOUNDED: bool = true;
+
+    fn is_zero(&self) -> bool {
+        *self == 0
+    }
+
+    fn checked_add(&self, other: &Self) -> Option<Self> {
+        u64::checked_add(*self, *other)
+    }
+
+    fn checked_sub(&self, other: &Self) -> Option<Self> {
+        u64::checked_sub(*self, *other)
+    }
+
+    fn checked_mul(&self, other: &Self) -> Option<Self> {
+        u64::checked_mul(*self, *other)
+    }
+
+    fn checked_div_rem(&self, other: &Self) -> Option<(Self, Self)> {
+        Some((self.checked_div(*other)?, self.checked_rem(*other)?))
+    }
+
+    fn checked_pow(&self, exponent: &Self) -> Result<Self, EvaluationError> {
+        match (*self, *exponent) {
+            (_, 0) => Ok(1),
+            (0 | 1, _) => Ok(*self),
+            // Any larger base to an exponent past `u32::MAX` overflows.
+            (base, exponent) => u32::try_from(exponent)
+                .ok()
+                .and_then(|exponent| base.checked_pow(exponent))
+                .ok_or(EvaluationError::Overflow),
+        }
+    }
+}
+
+const MAX_BIG_POWER_BITS: u64 = 1 << 16;
+
+impl WorksheetNumber for BigUint {
+    const IS_BOUNDED: bool = false;
+
+    fn is_zero(&self) -> bool {
+        BigUint::is_zero(self)
+    }
+
+    fn checked_add(&self, other: &Self) -> Option<Self> {
+        Some(self + other)
+    }
+
+    fn checked_sub(&self, other: &Self) -> Option<Self> {
+        BigUint::checked_sub(self, other)
+    }
+
+    fn checked_mul(&self, other: &Self) -> Option<Self> {
+        Some(self * other)
+    }
+
+    fn checked_div_rem(&self, other: &Self) -> Option<(Self, Self)> {
+        BigUint::checked_div_rem(self, other)
+    }
+
+    // Powers are refused past `MAX_BIG_POWER_BITS`, chained exponents would
+    // otherwise quickly exhaust time and memory.
+    fn checked_pow(&self, exponent: &Self) -> Result<Self, EvaluationError> {
+        if exponent.is_zero() {
+            return Ok(BigUint::one());
+        }
+        if self.is_zero() || *self == BigUint::one() {
+            return Ok(self.clone());
+        }
+        let too_large =
+            || EvaluationError::ExponentTooLarge(self.to_string(), exponent.to_string());
+        let Some(exponent) = exponent.to_u64().and_then(|e| u32::try_from(e).ok()) else {
+            return Err(too_large());
+        };
+        if self.nb_bits() as u64 * exponent as u64 > MAX_BIG_POWER_BITS {
+            return Err(too_large());
+        }
+        Ok(self.pow(exponent))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EvaluationError {
+    NoNumber,
+    InvalidNumber(String),
+    NegativeResult(String, String),
+    DivisionByZero(String),
+    InexactDivision(String, String, String),
+    Overflow,
+    // The power would be exact but too large to be computed.
+    ExponentTooLarge(String, String),
+}
+
+impl Display for EvaluationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EvaluationError::NoNumber => write!(f, "no number to operate on"),
+            EvaluationError::InvalidNumber(digits) => write!(f, "invalid number {digits}"),
+            EvaluationError::NegativeResult(a, b) => write!(f, "{a} - {b} is negative"),
+            EvaluationError::DivisionByZero(a) => write!(f, "{a} divided by zero"),
+            EvaluationError::InexactDivision(a, b, remainder) => {
+                write!(f, "{a} / {b} leaves a remainder of {remainder}")
+            }
+            EvaluationError::Overflow => write!(f, "arithmetic overflow"),
+            EvaluationError::ExponentTooLarge(a, b) => {
+                write!(f, "{a} ^ {b} is too large to be computed")
+            }
+        }
+    }
+}
+
+// Operators of the worksheet, written `+`, `*`, `-`, `/`, `^`, `%`, `<` for the
+// minimum and `>` for the maximum.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operator {
+    Add,
+    Multiply,
+    Subtract,
+    // Exact integer division, a remainder is an error.
+    Divide,
+    Power,
+    Modulo,
+    Min,
+    Max,
+}
+
+impl Operator {
+    pub fn from_char(c: char) -> Option<Self> {
+        match c {
+            '+' => Some(Operator::Add),
+            '*' => Some(Operator::Multiply),
+            '-' => Some(Operator::Subtract),
+            '/' => Some(Operator::Divide),
+            '^' => Some(Operator::Power),
+            '%' => Some(Operator::Modulo),
+            '<' => Some(Operator::Min),
+            '>' => Some(Operator::Max),
+            _ => None,
+        }
+    }
+
+    pub fn symbol(&self) -> char {
+        match self {
+            Operator::Add => '+',
+            Operator::Multiply => '*',
+            Operator::Subtract => '-',
+            Operator::Divide => '/',
+            Operator::Power => '^',
+            Operator::Modulo => '%',
+            Operator::Min => '<',
+            Operator::Max => '>',
+        }
+    }
+
+    fn combine<T: WorksheetNumber>(&self, a: T, b: &T) -> Result<T, EvaluationError> {
+        match self {
+            Operator::Add => a.checked_add(b).ok_or(EvaluationError::Overflow),
+            Operator::Multiply => a.checked_mul(b).ok_or(EvaluationError::Overflow),
+            Operator::Subtract => a
+                .checked_sub(b)
+                .ok_or_else(|| EvaluationError::NegativeResult(a.to_string(), b.to_string())),
+            Operator::Divide => match a.checked_div_rem(b) {
+                None => Err(EvaluationError::DivisionByZero(a.to_string())),
+                Some((quotient, remainder)) => {
+                    if remainder.is_zero() {
+                        Ok(quotient)
+                    } else {
+                        Err(EvaluationError::InexactDivision(
+                            a.to_string(),
+                            b.to_string(),
+                            remainder.to_string(),
+                        ))
+                    }
+                }
+            },
+            Operator::Power => a.checked_pow(b),
+            Operator::Modulo => match a.checked_div_rem(b) {
+                None => Err(EvaluationError::DivisionByZero(a.to_string())),
+                Some((_, remainder)) => Ok(remainder),
+            },
+            Operator::Min => Ok(a.min(b.clone())),
+            Operator::Max => Ok(a.max(b.clone())),
+        }
+    }
+
+    // Numbers are combined from left to right, in the order they are read:
+    // `a - b - c` is `(a - b) - c` and `a ^ b ^ c` is `(a ^ b) ^ c`.
+    pub fn apply<T: WorksheetNumber>(&self, numbers: &[T]) -> Result<T, EvaluationError> {
+        let Some((first, others)) = numbers.split_first() else {
+            return Err(EvaluationError::NoNumber);
+        };
+        others
+            .iter()
+            .try_fold(first.clone(), |result, number| self.combine(result, number))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReadingOrder {
+    // Each row of the problem is a number.
+    RowWise,
+    // Each column of the problem is a number, the columns being read from
+    // right to left.
+    RightToLeftColumns,
+    LeftToRightColumns,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DigitOrder {
+    // Digits read from the left of rows, or from the top of columns.
+    MostSignificantFirst,
+    // Digits read from the right of rows, or from the bottom of columns.
+    LeastSignificantFirst,
+}
+
+// How the cells of a problem are read into numbers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReadingMode {
+    pub order: ReadingOrder,
+    pub digits: DigitOrder,
+}
+
+impl ReadingMode {
+    pub const ROW_WISE: Self = Self {
+        order: ReadingOrder::RowWise,
+        digits: DigitOrder::MostSignificantFirst,
+    };
+    pub const CEPHALOPOD: Self = Self {
+        order: ReadingOrder::RightToLeftColumns,
+        digits: DigitOrder::MostSignificantFirst,
+    };
+}
+
+// Row holding the operators of the worksheet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OperatorPosition {
+    Bottom,
+    Top,
+}
+
+impl OperatorPosition {
+    // Index of the operator row among `height` rows, `None` without rows.
+    pub fn operator_row(&self, height: usize) -> Option<usize> {
+        match (self, height) {
+            (_, 0) => None,
+            (OperatorPosition::Bottom, _) => Some(height - 1),
+            (OperatorPosition::Top, _) => Some(0),
+        }
+    }
+
+    pub fn digit_rows(&self, height: usize) -> Range<usize> {
+        match self {
+            OperatorPosition::Bottom => 0..height.saturating_sub(1),
+            OperatorPosition::Top => height.min(1)..height,
+        }
+    }
+}
+
+// One problem of the worksheet: the block of columns between two separator
+// columns made only of spaces.
+#[derive(Debug, Clone)]
+pub struct Problem {
+    // Columns of the worksheet covered by the problem.
+    pub columns: Range<usize>,
+    pub operator: Operator,
+    // Column of the operator, relative to the first column of the problem.
+    pub operator_column: usize,
+    pub operator_position: OperatorPosition,
+    // The digit rows of the problem, without the operator row.
+    pub cells: Grid<char>,
+}
+
+impl Problem {
+    // Positions in `cells` of the digits of each number, in the order they are
+    // read, the numbers being listed in reading order.
+    pub fn digit_positions(&self, mode: ReadingMode) -> Vec<Vec<(usize, usize)>> {
+        let mut lines: Vec<Vec<(usize, usize)>> = match mode.order {
+            ReadingOrder::RowWise => (0..self.cells.height())
+                .map(|row| (0..self.cells.width()).map(|col| (row, col)).collect())
+                .collect(),
+            ReadingOrder::RightToLeftColumns => (0..self.cells.width())
+                .rev()
+                .map(|col| (0..self.cells.height()).map(|row| (row, col)).collect())
+                .collect(),
+            ReadingOrder::LeftToRightColumns => (0..self.cells.width())
+                .map(|col| (0..self.cells.height()).map(|row| (row, col)).collect())
+                .collect(),
+        };
+        if mode.digits == DigitOrder::LeastSignificantFirst {
+            lines.iter_mut().for_each(|line| line.reverse());
+        }
+
+        lines
+            .into_iter()
+            .map(|line| {
+                line.into_iter()
+                    .filter(|&pos| !self.cells[pos].is_whitespace())
+                    .collect::<Vec<(usize, usize)>>()
+            })
+            .filter(|positions| !positions.is_empty())
+            .collect()
+    }
+
+    // The numbers as written, in reading order.
+    pub fn digit_groups(&self, mode: ReadingMode) -> Vec<String> {
+        self.digit_positions(mode)
+            .iter()
+            .map(|positions| positions.iter().map(|&pos| self.cells[pos]).collect())
+            .collect()
+    }
+
+    pub fn numbers<T: WorksheetNumber>(
+        &self,
+        mode: ReadingMode,
+    ) -> Result<Vec<T>, EvaluationError> {
+        self.digit_groups(mode)
+            .into_iter()
+            .map(|digits| match digits.parse::<T>() {
+                Ok(number) => Ok(number),
+                // Only digits but still not a number, it's too large.
+                Err(_) if digits.chars().all(|c| c.is_ascii_digit()) => {
+                    Err(EvaluationError::Overflow)
+                }
+                Err(_) => Err(EvaluationError::InvalidNumber(digits)),
+            })
+            .collect()
+    }
+
+    pub fn evaluate<T: WorksheetNumber>(&self, mode: ReadingMode) -> Result<T, EvaluationError> {
+        self.operator.apply(&self.numbers::<T>(mode)?)
+    }
+}
+
+// The worksheet split once into its problems, from left to right. The reading
+// modes are only interpretations of the cells of each problem.
+pub struct Worksheet {
+    pub problems: Vec<Problem>,
+}
+
+// Columns of each problem of the worksheet, problems being separated by
+// columns made only of spaces.
+pub fn problem_blocks(grid: &Grid<char>) -> Vec<Range<usize>> {
+    let mut blocks: Vec<Range<usize>> = vec![];
+    let mut block_start: Option<usize> = None;
+    for col in 0..grid.width() {
+        let is_separator = grid.column(col).all(|&c| c == ' ');
+        match (is_separator, block_start) {
+            (false, None) => block_start = Some(col),
+            (true, Some(start)) => {
+                blocks.push(start..col);
+                block_start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(start) = block_start {
+        blocks.push(start..grid.width());
+    }
+    blocks
+}
+
+impl Worksheet {
+    pub fn parse(lines: &[String], operator_position: OperatorPosition) -> Self {
+        let grid = Grid::parse(lines, ' ', |c| c);
+        let Some(operator_row) = operator_position.operator_row(grid.height()) else {
+            panic!("Empty worksheet!");
+        };
+
+        let problems = problem_blocks(&grid)
+            .into_iter()
+            .map(|columns| {
+                let operators: Vec<(usize, char)> = grid.row(operator_row)[columns.clone()]
+                    .iter()
+                    .copied()
+                    .enumerate()
+                    .filter(|&(_, c)| c != ' ')
+                    .collect();
+                let [(operator_column, operator)] = operators.as_slice() else {
+                    panic!(
+                        "Problem at columns {}-{} should have exactly one operator, found {:?}",
+                        columns.start,
+                        columns.end - 1,
+                        operators.iter().map(|&(_, c)| c).collect::<Vec<char>>()
+                    );
+                };
+                let Some(operator) = Operator::from_char(*operator) else {
+                    panic!("Unknown operator {operator}");
+                };
+                Problem {
+                    cells: grid
+                        .sub_grid(operator_position.digit_rows(grid.height()), columns.clone()),
+                    columns,
+                    operator,
+                    operator_column: *operator_column,
+                    operator_position,
+                }
+            })
+            .collect();
+
+        Self { problems }
+    }
+}
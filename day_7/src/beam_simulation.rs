@@ -0,0 +1,168 @@
+use common::Grid;
+
+use crate::splitters::is_splitter;
+
+// Set of columns of a manifold row, one bit per column. Column `col` is bit
+// `col % 64` of word `col / 64`, the padding bits of the last word are always
+// cleared.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ColumnSet {
+    width: usize,
+    words: Vec<u64>,
+}
+
+impl ColumnSet {
+    fn new(width: usize) -> Self {
+        Self {
+            width,
+            words: vec![0; width.div_ceil(64)],
+        }
+    }
+
+    fn from_columns(width: usize, columns: impl Iterator<Item = usize>) -> Self {
+        let mut set = Self::new(width);
+        for col in columns {
+            set.words[col / 64] |= 1 << (col % 64);
+        }
+        set
+    }
+
+    fn zip_with(&self, other: &Self, f: impl Fn(u64, u64) -> u64) -> Self {
+        Self {
+            width: self.width,
+            words: self
+                .words
+                .iter()
+                .zip(other.words.iter())
+                .map(|(&a, &b)| f(a, b))
+                .collect(),
+        }
+    }
+
+    fn intersection(&self, other: &Self) -> Self {
+        self.zip_with(other, |a, b| a & b)
+    }
+
+    fn difference(&self, other: &Self) -> Self {
+        self.zip_with(other, |a, b| a & !b)
+    }
+
+    fn union(&self, other: &Self) -> Self {
+        self.zip_with(other, |a, b| a | b)
+    }
+
+    // Every column moved one step to the left, column 0 falling off.
+    fn shifted_left(&self) -> Self {
+        let words = (0..self.words.len())
+            .map(|idx| {
+                let carry = self.words.get(idx + 1).map_or(0, |w| w << 63);
+                (self.words[idx] >> 1) | carry
+            })
+            .collect();
+        Self {
+            width: self.width,
+            words,
+        }
+    }
+
+    // Every column moved one step to the right, the last column falling off.
+    fn shifted_right(&self) -> Self {
+        let mut words: Vec<u64> = (0..self.words.len())
+            .map(|idx| {
+                let carry = if idx > 0 {
+                    self.words[idx - 1] >> 63
+                } else {
+                    0
+                };
+                (self.words[idx] << 1) | carry
+            })
+            .collect();
+        if !self.width.is_multiple_of(64)
+            && let Some(last) = words.last_mut()
+        {
+            *last &= (1 << (self.width % 64)) - 1;
+        }
+        Self {
+            width: self.width,
+            words,
+        }
+    }
+
+    fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words.iter().enumerate().flat_map(|(idx, &word)| {
+            (0..64)
+                .filter(move |bit| (word >> bit) & 1 == 1)
+                .map(move |bit| idx * 64 + bit)
+        })
+    }
+}
+
+// Outcome of the classical beam simulation.
+pub struct BeamSimulation {
+    // Splitters positions as (row, col), from top to bottom and left to right.
+    pub splitters_hit: Vec<(usize, usize)>,
+    pub splitters_never_reached: Vec<(usize, usize)>,
+    // Columns of the beams leaving the manifold at the bottom.
+    pub exit_columns: Vec<usize>,
+    // The manifold with the path of the beams drawn as `|`, when requested.
+    pub diagram: Option<Grid<char>>,
+}
+
+impl BeamSimulation {
+    // Beams merge, so a splitter splits at most once.
+    pub fn nb_splits(&self) -> usize {
+        self.splitters_hit.len()
+    }
+}
+
+// Moves the beams down one row at a time, the beams of a row being a set of
+// columns: beams reaching a splitter are replaced by beams on both sides of it,
+// the other ones go on straight down. Each source adds a beam in its row.
+pub fn simulate_beams(manifold: &Grid<char>, with_diagram: bool) -> BeamSimulation {
+    let width = manifold.width();
+    let Some(start_row) = (0..manifold.height()).find(|&row| manifold.row(row).contains(&'S'))
+    else {
+        panic!("No start found!");
+    };
+    let mut beams = ColumnSet::new(width);
+    let mut diagram = with_diagram.then(|| manifold.clone());
+    let mut splitters_hit: Vec<(usize, usize)> = vec![];
+
+    for row in start_row..manifold.height() {
+        let sources =
+            ColumnSet::from_columns(width, (0..width).filter(|&col| manifold[(row, col)] == 'S'));
+        beams = beams.union(&sources);
+        let splitters = ColumnSet::from_columns(
+            width,
+            (0..width).filter(|&col| is_splitter(manifold[(row, col)])),
+        );
+        let hit = beams.intersection(&splitters);
+        splitters_hit.extend(hit.iter().map(|col| (row, col)));
+        beams = beams
+            .difference(&hit)
+            .union(&hit.shifted_left())
+            .union(&hit.shifted_right());
+
+        if let Some(diagram) = diagram.as_mut() {
+            for col in beams.iter() {
+                if diagram[(row, col)] == '.' {
+                    diagram[(row, col)] = '|';
+                }
+            }
+        }
+    }
+
+    let splitters_never_reached = manifold
+        .iter()
+        // Splitters are hit in row-major order, `splitters_hit` is sorted.
+        .filter(|&(pos, &c)| is_splitter(c) && splitters_hit.binary_search(&pos).is_err())
+        .map(|(pos, _)| pos)
+        .collect();
+
+    BeamSimulation {
+        splitters_hit,
+        splitters_never_reached,
+        exit_columns: beams.iter().collect(),
+        diagram,
+    }
+}
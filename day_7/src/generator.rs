@@ -0,0 +1,52 @@
+// SplitMix64 generator, enough to draw reproducible manifolds from a seed.
+struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    // Uniform in [0, 1).
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+// Manifold laid out like the puzzle inputs: the start in the middle of the
+// first row, then splitter rows alternating with empty rows. Each cell of a
+// splitter row holds a splitter with probability `density`.
+pub fn generate_manifold(width: usize, height: usize, density: f64, seed: u64) -> Vec<String> {
+    if width == 0 || height == 0 {
+        panic!("Can't generate a manifold of {width}x{height}");
+    }
+    if !(0.0..=1.0).contains(&density) {
+        panic!("Splitter density {density} is not between 0 and 1");
+    }
+
+    let mut rng = SplitMix64::new(seed);
+    let mut lines: Vec<String> = Vec::with_capacity(height);
+    let mut start_row = ".".repeat(width);
+    start_row.replace_range(width / 2..width / 2 + 1, "S");
+    lines.push(start_row);
+    for row in 1..height {
+        let line = if row % 2 == 0 {
+            (0..width)
+                .map(|_| if rng.next_f64() < density { '^' } else { '.' })
+                .collect()
+        } else {
+            ".".repeat(width)
+        };
+        lines.push(line);
+    }
+    lines
+}
@@ -235,90 +235,287 @@
 // manifold diagram. In total, how many different timelines would a single
 // tachyon particle end up on?
 
-use std::{collections::HashMap, io::BufRead};
-
-fn part1_solver(lines: &[String]) {
-    let width: usize = lines.iter().map(|l| l.len()).max().unwrap_or(0);
-    let mut beams_pos: Vec<usize> = vec![];
-    let mut nb_splitting: u64 = 0;
-
-    for line in lines {
-        if beams_pos.is_empty() {
-            println!("{line}");
-            let Some(start_pos) = line.find("S") else {
-                panic!("No start found!");
-            };
-            beams_pos.push(start_pos);
-        } else {
-            let mut new_line = line.clone();
-            for (pos, cell) in line.chars().enumerate() {
-                if cell == '^' && beams_pos.contains(&pos) {
-                    if pos > 0 && !beams_pos.contains(&(pos - 1)) {
-                        beams_pos.push(pos - 1);
-                        new_line.replace_range((pos - 1)..pos, "|");
-                    }
-                    if pos < width - 1 && !beams_pos.contains(&(pos + 1)) {
-                        beams_pos.push(pos + 1);
-                        new_line.replace_range((pos + 1)..(pos + 2), "|");
-                    }
-                    let Some(beam_idx) = beams_pos.iter().position(|p| *p == pos) else {
-                        panic!("Can't retrieve position of current beam in the list!");
-                    };
-                    beams_pos.remove(beam_idx);
-                    nb_splitting += 1;
-                } else if beams_pos.contains(&pos) {
-                    new_line.replace_range(pos..(pos + 1), "|");
-                }
-            }
-            println!("{new_line}");
+mod beam_simulation;
+mod generator;
+mod optics;
+mod probabilities;
+mod rational;
+mod splitters;
+mod timelines;
+
+use std::{
+    io::BufRead,
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+use beam_simulation::simulate_beams;
+use common::Grid;
+use generator::generate_manifold;
+use optics::{OpticalManifold, has_optical_components};
+use probabilities::exit_distribution;
+use timelines::Timelines;
+
+fn optical_part1_solver(manifold: &Grid<char>) {
+    let simulation = OpticalManifold::new(manifold).simulate();
+    if let Some(beam) = simulation.loop_at {
+        println!(
+            "Beams loop through cell ({}, {}) going {:?}",
+            beam.row, beam.col, beam.direction
+        );
+    }
+    println!("{} cells energised", simulation.nb_energised);
+    for exit in simulation.exits.iter() {
+        println!(
+            "Beam leaving the manifold from cell ({}, {}) going {:?}",
+            exit.row, exit.col, exit.direction
+        );
+    }
+    println!(
+        "[PART 1] Beam has been splited {} times",
+        simulation.nb_splits
+    );
+}
+
+fn part1_solver(lines: &[String], with_diagram: bool) {
+    let manifold = Grid::parse(lines, '.', |c| c);
+    if has_optical_components(&manifold) {
+        optical_part1_solver(&manifold);
+        return;
+    }
+    let simulation = simulate_beams(&manifold, with_diagram);
+    if let Some(diagram) = &simulation.diagram {
+        print!("{diagram}");
+    }
+    println!(
+        "{} splitters hit, {} never reached",
+        simulation.splitters_hit.len(),
+        simulation.splitters_never_reached.len()
+    );
+    println!(
+        "{} beams leaving the manifold at columns {:?}",
+        simulation.exit_columns.len(),
+        simulation.exit_columns
+    );
+    println!(
+        "[PART 1] Beam has been splited {} times",
+        simulation.nb_splits()
+    );
+}
+
+fn optical_part2_solver(manifold: &Grid<char>, through: &[(usize, usize)]) {
+    let timelines = match OpticalManifold::new(manifold).count_timelines() {
+        Ok(timelines) => timelines,
+        Err(beam) => {
+            println!(
+                "[PART 2] Timelines never end, beams loop through cell ({}, {}) going {:?}",
+                beam.row, beam.col, beam.direction
+            );
+            return;
         }
+    };
+    for (exit, count) in timelines.exits.iter() {
+        println!(
+            "{count} timelines leaving from cell ({}, {}) going {:?}",
+            exit.row, exit.col, exit.direction
+        );
     }
-    println!("[PART 1] Beam has been splited {nb_splitting} times");
+    for &(row, col) in through {
+        let Some(count) = timelines.through.get(row, col) else {
+            panic!("Cell ({row}, {col}) is out of the manifold");
+        };
+        println!("{count} timelines going through cell ({row}, {col})");
+    }
+    println!(
+        "[PART 2] Number of alternative timelines {}",
+        timelines.total()
+    );
 }
 
-fn travel_tree(
-    lines: &[String],
-    row: usize,
-    beam_pos: usize,
-    memo: &mut HashMap<(usize, usize), u64>,
-    width: usize,
-) -> u64 {
-    if row >= lines.len() {
-        return 1;
+fn part2_solver(lines: &[String], through: &[(usize, usize)]) {
+    let manifold = Grid::parse(lines, '.', |c| c);
+    if !manifold.iter().any(|(_, &c)| c == 'S') {
+        panic!("No start found");
+    }
+    if has_optical_components(&manifold) {
+        optical_part2_solver(&manifold, through);
+        return;
+    }
+    let timelines = Timelines::count(&manifold);
+    for (col, count) in timelines.exits().iter().enumerate() {
+        if !count.is_zero() {
+            println!("{count} timelines leaving at column {col}");
+        }
+    }
+    for &(row, col) in through {
+        let Some(count) = timelines.through(row, col) else {
+            panic!("Cell ({row}, {col}) is out of the manifold");
+        };
+        println!("{count} timelines going through cell ({row}, {col})");
     }
+    println!(
+        "[PART 2] Number of alternative timelines {}",
+        timelines.total()
+    );
+}
 
-    if let Some(&result) = memo.get(&(row, beam_pos)) {
-        return result;
+fn probabilities_solver(lines: &[String]) {
+    let manifold = Grid::parse(lines, '.', |c| c);
+    if has_optical_components(&manifold) {
+        panic!("Exit probabilities are only computed for manifolds where beams go down");
+    }
+    let distribution = exit_distribution(&manifold);
+    for (col, probability) in distribution.exits.iter().enumerate() {
+        if !probability.is_zero() {
+            println!("Probability {probability} to leave at column {col}");
+        }
     }
+    if !distribution.lost.is_zero() {
+        println!("Probability {} to be lost on the sides", distribution.lost);
+    }
+}
+
+struct GeneratorSettings {
+    width: usize,
+    height: usize,
+    density: f64,
+    seed: u64,
+}
 
-    let current_line = &lines[row];
-    let mut count = 0;
-    if current_line.chars().nth(beam_pos) == Some('^') {
-        if beam_pos + 1 < width {
-            count += travel_tree(lines, row + 1, beam_pos + 1, memo, width);
+// Solves generated manifolds with both the down-only solvers and the optical
+// engine, panicking with the seed of the first manifold they disagree on.
+fn cross_check_solvers(settings: &GeneratorSettings, count: u64) {
+    let mut bit_packed_time = Duration::ZERO;
+    let mut row_dp_time = Duration::ZERO;
+    let mut optical_time = Duration::ZERO;
+
+    for seed in settings.seed..settings.seed + count {
+        let lines = generate_manifold(settings.width, settings.height, settings.density, seed);
+        let manifold = Grid::parse(&lines, '.', |c| c);
+
+        let start = Instant::now();
+        let simulation = simulate_beams(&manifold, false);
+        bit_packed_time += start.elapsed();
+
+        let start = Instant::now();
+        let timelines = Timelines::count(&manifold);
+        row_dp_time += start.elapsed();
+
+        let start = Instant::now();
+        let optical = OpticalManifold::new(&manifold);
+        let optical_simulation = optical.simulate();
+        let Ok(optical_timelines) = optical.count_timelines() else {
+            panic!("Seed {seed}: beams loop in a down-only manifold");
+        };
+        optical_time += start.elapsed();
+
+        let optical_exit_columns: Vec<usize> = optical_simulation
+            .exits
+            .iter()
+            .map(|exit| exit.col)
+            .collect();
+        let timeline_exit_columns: Vec<usize> = (0..manifold.width())
+            .filter(|&col| !timelines.exits()[col].is_zero())
+            .collect();
+        if simulation.nb_splits() != optical_simulation.nb_splits {
+            panic!(
+                "Seed {seed}: {} splits with the bitset, {} with the optical engine",
+                simulation.nb_splits(),
+                optical_simulation.nb_splits
+            );
+        }
+        if simulation.exit_columns != optical_exit_columns
+            || simulation.exit_columns != timeline_exit_columns
+        {
+            panic!(
+                "Seed {seed}: beams leave at columns {:?} with the bitset, {:?} with the optical \
+                 engine and {:?} with the timelines",
+                simulation.exit_columns, optical_exit_columns, timeline_exit_columns
+            );
         }
-        if beam_pos > 0 {
-            count += travel_tree(lines, row + 1, beam_pos - 1, memo, width);
+        if timelines.total() != optical_timelines.total() {
+            panic!(
+                "Seed {seed}: {} timelines with the row DP, {} with the optical engine",
+                timelines.total(),
+                optical_timelines.total()
+            );
         }
-    } else {
-        count = travel_tree(lines, row + 1, beam_pos, memo, width)
+    }
+
+    println!(
+        "{count} manifolds of {}x{} checked, bitset simulation {bit_packed_time:?}, \
+         timelines row DP {row_dp_time:?}, optical engine {optical_time:?}",
+        settings.width, settings.height
+    );
+}
+
+fn parse_value<T: FromStr>(value: Option<String>, name: &str) -> T {
+    let Some(Ok(parsed)) = value.as_deref().map(|v| v.parse::<T>()) else {
+        panic!("Invalid {name} {value:?}");
     };
+    parsed
+}
 
-    memo.insert((row, beam_pos), count);
-    count
+fn parse_generator_settings(args: &mut impl Iterator<Item = String>) -> GeneratorSettings {
+    GeneratorSettings {
+        width: parse_value(args.next(), "width"),
+        height: parse_value(args.next(), "height"),
+        density: parse_value(args.next(), "density"),
+        seed: parse_value(args.next(), "seed"),
+    }
 }
 
-fn part2_solver(lines: &[String]) {
-    let width: usize = lines.iter().map(|l| l.len()).max().unwrap_or(0);
-    let Some(beam_start) = lines[0].find("S") else {
-        panic!("No start found");
+fn parse_cell(value: Option<String>) -> (usize, usize) {
+    let Some((Ok(row), Ok(col))) = value.as_deref().and_then(|v| {
+        v.split_once(',')
+            .map(|(row, col)| (row.parse::<usize>(), col.parse::<usize>()))
+    }) else {
+        panic!("Invalid cell {value:?}, expected ROW,COL");
     };
-    let mut memo = HashMap::new();
-    let nb_alternative_timelines = travel_tree(lines, 1, beam_start, &mut memo, width);
-    println!("[PART 2] Number of alternative timelines {nb_alternative_timelines}");
+    (row, col)
 }
 
+const USAGE: &str = "Usage: day_7 [--diagram] [--probabilities] [--through ROW,COL]...\n       \
+                     day_7 --generate WIDTH HEIGHT DENSITY SEED\n       \
+                     day_7 --cross-check WIDTH HEIGHT DENSITY SEED COUNT";
+
 fn main() {
+    // `--diagram` prints the manifold with the path of the beams, each
+    // `--through ROW,COL` the number of timelines going through a cell and
+    // `--probabilities` the probability to leave the manifold at each column.
+    // `--generate` prints a random manifold instead of solving `input.txt`,
+    // `--cross-check` compares the solvers on COUNT random manifolds.
+    let mut with_diagram = false;
+    let mut with_probabilities = false;
+    let mut through: Vec<(usize, usize)> = vec![];
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--diagram" => with_diagram = true,
+            "--through" => through.push(parse_cell(args.next())),
+            "--probabilities" => with_probabilities = true,
+            "--generate" => {
+                let settings = parse_generator_settings(&mut args);
+                let lines = generate_manifold(
+                    settings.width,
+                    settings.height,
+                    settings.density,
+                    settings.seed,
+                );
+                for line in lines {
+                    println!("{line}");
+                }
+                return;
+            }
+            "--cross-check" => {
+                let settings = parse_generator_settings(&mut args);
+                cross_check_solvers(&settings, parse_value(args.next(), "count"));
+                return;
+            }
+            _ => panic!("{USAGE}"),
+        }
+    }
+
     let Ok(file) = std::fs::File::open("input.txt") else {
         panic!("Failed to open file: `input.txt`");
     };
@@ -326,6 +523,9 @@ fn main() {
     let reader = std::io::BufReader::new(file);
     let lines: Vec<String> = reader.lines().map(|l| l.unwrap()).collect();
 
-    part1_solver(&lines);
-    part2_solver(&lines);
+    part1_solver(&lines, with_diagram);
+    part2_solver(&lines, &through);
+    if with_probabilities {
+        probabilities_solver(&lines);
+    }
 }
@@ -0,0 +1,311 @@
+use std::collections::{BTreeMap, HashMap};
+
+use common::{BigUint, Grid};
+
+use crate::splitters::is_splitter;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Direction {
+    fn offset(self) -> (isize, isize) {
+        match self {
+            Direction::Up => (-1, 0),
+            Direction::Down => (1, 0),
+            Direction::Left => (0, -1),
+            Direction::Right => (0, 1),
+        }
+    }
+
+    fn is_vertical(self) -> bool {
+        matches!(self, Direction::Up | Direction::Down)
+    }
+
+    fn perpendiculars(self) -> [Direction; 2] {
+        if self.is_vertical() {
+            [Direction::Left, Direction::Right]
+        } else {
+            [Direction::Up, Direction::Down]
+        }
+    }
+
+    // Direction after bouncing on a `/` mirror.
+    fn reflect_slash(self) -> Self {
+        match self {
+            Direction::Up => Direction::Right,
+            Direction::Down => Direction::Left,
+            Direction::Left => Direction::Down,
+            Direction::Right => Direction::Up,
+        }
+    }
+
+    // Direction after bouncing on a `\` mirror.
+    fn reflect_backslash(self) -> Self {
+        match self {
+            Direction::Up => Direction::Left,
+            Direction::Down => Direction::Right,
+            Direction::Left => Direction::Up,
+            Direction::Right => Direction::Down,
+        }
+    }
+}
+
+// A beam in the cell (row, col) moving in the given direction. For a beam
+// leaving the manifold, the last cell it went through.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Beam {
+    pub row: usize,
+    pub col: usize,
+    pub direction: Direction,
+}
+
+enum Successor {
+    Beam(Beam),
+    Exit(Beam),
+}
+
+// Whether the manifold uses more than the `.`, `S` and splitter cells the
+// down-only solvers know about.
+pub fn has_optical_components(manifold: &Grid<char>) -> bool {
+    manifold
+        .iter()
+        .any(|(_, &c)| !matches!(c, '.' | 'S') && !is_splitter(c))
+}
+
+// Outcome of the classical simulation, beams merging when they meet.
+pub struct OpticalSimulation {
+    // Splitters that split at least one beam.
+    pub nb_splits: usize,
+    // Cells at least one beam went through.
+    pub nb_energised: usize,
+    pub exits: Vec<Beam>,
+    // A beam going round a loop, if any.
+    pub loop_at: Option<Beam>,
+}
+
+// Outcome of the quantum mode, every split starting new timelines.
+pub struct OpticalTimelines {
+    // Number of timelines leaving the manifold from each cell and direction.
+    pub exits: BTreeMap<Beam, BigUint>,
+    // Number of timelines going through each cell, in any direction.
+    pub through: Grid<BigUint>,
+}
+
+impl OpticalTimelines {
+    pub fn total(&self) -> BigUint {
+        self.exits.values().cloned().sum()
+    }
+}
+
+// Manifold where beams travel in the four directions:
+// - `S` sources emit a beam going down,
+// - `/` and `\` mirrors turn beams by a quarter,
+// - `|` splits horizontal beams up and down, `-` splits vertical beams left and
+//   right, beams parallel to them go through,
+// - `^` and weighted splitters split any beam into two beams on both sides of
+//   it, going on in the same direction as for the down-only manifolds,
+// - `#` absorbs beams.
+pub struct OpticalManifold<'a> {
+    grid: &'a Grid<char>,
+}
+
+impl<'a> OpticalManifold<'a> {
+    pub fn new(grid: &'a Grid<char>) -> Self {
+        Self { grid }
+    }
+
+    fn step(&self, row: usize, col: usize, direction: Direction) -> Option<(usize, usize)> {
+        let (dr, dc) = direction.offset();
+        let (new_row, new_col) = (row as isize + dr, col as isize + dc);
+        if self.grid.in_bounds(new_row, new_col) {
+            Some((new_row as usize, new_col as usize))
+        } else {
+            None
+        }
+    }
+
+    fn sources(&self) -> Vec<Beam> {
+        self.grid
+            .iter()
+            .filter(|&(_, &c)| c == 'S')
+            .map(|((row, col), _)| Beam {
+                row,
+                col,
+                direction: Direction::Down,
+            })
+            .collect()
+    }
+
+    fn splits(&self, beam: Beam) -> bool {
+        match self.grid[(beam.row, beam.col)] {
+            c if is_splitter(c) => true,
+            '|' => !beam.direction.is_vertical(),
+            '-' => beam.direction.is_vertical(),
+            _ => false,
+        }
+    }
+
+    // Cells the beam leaves from and the direction it leaves them in. Beams
+    // split by `^` leave from the cells on its sides, beams whose side is out
+    // of the manifold being lost.
+    fn moves(&self, beam: Beam) -> Vec<(usize, usize, Direction)> {
+        let Beam {
+            row,
+            col,
+            direction,
+        } = beam;
+        match self.grid[(row, col)] {
+            '#' => vec![],
+            '/' => vec![(row, col, direction.reflect_slash())],
+            '\\' => vec![(row, col, direction.reflect_backslash())],
+            '|' | '-' if self.splits(beam) => direction
+                .perpendiculars()
+                .into_iter()
+                .map(|side| (row, col, side))
+                .collect(),
+            c if is_splitter(c) => direction
+                .perpendiculars()
+                .into_iter()
+                .filter_map(|side| self.step(row, col, side))
+                .map(|(side_row, side_col)| (side_row, side_col, direction))
+                .collect(),
+            _ => vec![(row, col, direction)],
+        }
+    }
+
+    fn successors(&self, beam: Beam) -> Vec<Successor> {
+        self.moves(beam)
+            .into_iter()
+            .map(
+                |(row, col, direction)| match self.step(row, col, direction) {
+                    Some((row, col)) => Successor::Beam(Beam {
+                        row,
+                        col,
+                        direction,
+                    }),
+                    None => Successor::Exit(Beam {
+                        row,
+                        col,
+                        direction,
+                    }),
+                },
+            )
+            .collect()
+    }
+
+    fn next_beams(&self, beam: Beam) -> Vec<Beam> {
+        self.successors(beam)
+            .into_iter()
+            .filter_map(|successor| match successor {
+                Successor::Beam(next) => Some(next),
+                Successor::Exit(_) => None,
+            })
+            .collect()
+    }
+
+    // Depth-first search of the beams reachable from the sources. Returns them
+    // in topological order when there is no loop, otherwise a beam going back
+    // to one of its ancestors.
+    fn explore(&self) -> (Vec<Beam>, Option<Beam>) {
+        // `false` while the beam is on the search stack, `true` once done.
+        let mut done: HashMap<Beam, bool> = HashMap::new();
+        let mut postorder: Vec<Beam> = vec![];
+        let mut loop_at: Option<Beam> = None;
+
+        for source in self.sources() {
+            if done.contains_key(&source) {
+                continue;
+            }
+            done.insert(source, false);
+            let mut stack = vec![(source, self.next_beams(source), 0)];
+            while let Some((beam, next_beams, idx)) = stack.last_mut() {
+                let beam = *beam;
+                let Some(&next) = next_beams.get(*idx) else {
+                    done.insert(beam, true);
+                    postorder.push(beam);
+                    stack.pop();
+                    continue;
+                };
+                *idx += 1;
+                match done.get(&next) {
+                    None => {
+                        done.insert(next, false);
+                        stack.push((next, self.next_beams(next), 0));
+                    }
+                    Some(false) => {
+                        loop_at.get_or_insert(next);
+                    }
+                    Some(true) => {}
+                }
+            }
+        }
+
+        postorder.reverse();
+        (postorder, loop_at)
+    }
+
+    pub fn simulate(&self) -> OpticalSimulation {
+        let (beams, loop_at) = self.explore();
+        let mut energised = Grid::new(self.grid.width(), self.grid.height(), false);
+        let mut splitters = Grid::new(self.grid.width(), self.grid.height(), false);
+        let mut exits: Vec<Beam> = vec![];
+        for &beam in beams.iter() {
+            energised[(beam.row, beam.col)] = true;
+            if self.splits(beam) {
+                splitters[(beam.row, beam.col)] = true;
+            }
+            for (row, col, _) in self.moves(beam) {
+                energised[(row, col)] = true;
+            }
+            for successor in self.successors(beam) {
+                if let Successor::Exit(exit) = successor {
+                    exits.push(exit);
+                }
+            }
+        }
+        exits.sort();
+        exits.dedup();
+
+        OpticalSimulation {
+            nb_splits: splitters.iter().filter(|&(_, &split)| split).count(),
+            nb_energised: energised.iter().filter(|&(_, &lit)| lit).count(),
+            exits,
+            loop_at,
+        }
+    }
+
+    // Counts the timelines along the beams in topological order. A loop makes
+    // for never ending timelines, the looping beam is then returned instead.
+    pub fn count_timelines(&self) -> Result<OpticalTimelines, Beam> {
+        let (beams, loop_at) = self.explore();
+        if let Some(beam) = loop_at {
+            return Err(beam);
+        }
+
+        let mut counts: HashMap<Beam, BigUint> = HashMap::new();
+        for source in self.sources() {
+            *counts.entry(source).or_default() += &BigUint::one();
+        }
+        let mut exits: BTreeMap<Beam, BigUint> = BTreeMap::new();
+        let mut through = Grid::new(self.grid.width(), self.grid.height(), BigUint::zero());
+        for beam in beams {
+            let Some(count) = counts.remove(&beam) else {
+                continue;
+            };
+            for successor in self.successors(beam) {
+                match successor {
+                    Successor::Beam(next) => *counts.entry(next).or_default() += &count,
+                    Successor::Exit(exit) => *exits.entry(exit).or_default() += &count,
+                }
+            }
+            through[(beam.row, beam.col)] += &count;
+        }
+
+        Ok(OpticalTimelines { exits, through })
+    }
+}
@@ -0,0 +1,73 @@
+use common::{BigUint, Grid};
+
+use crate::rational::Rational;
+use crate::splitters::{is_splitter, left_tenths};
+
+// Probability for a beam to leave the manifold below each column, one of the
+// sources being picked uniformly. Beams sent off the sides of the manifold by
+// a splitter are lost.
+pub struct ExitDistribution {
+    pub exits: Vec<Rational>,
+    pub lost: Rational,
+}
+
+// Row by row like the timelines count, probabilities sharing a common
+// denominator: the number of sources times a power of ten, scaled up by ten
+// on each row with a splitter so that the weights in tenths stay integers.
+// Fractions are only reduced at the end.
+pub fn exit_distribution(manifold: &Grid<char>) -> ExitDistribution {
+    let width = manifold.width();
+    let nb_sources = manifold.iter().filter(|&(_, &c)| c == 'S').count();
+    if nb_sources == 0 {
+        panic!("No start found");
+    }
+    let ten = BigUint::from(10);
+    // Common denominator is `nb_sources * scale`.
+    let mut scale = BigUint::one();
+    let mut arriving = vec![BigUint::zero(); width];
+    let mut lost = BigUint::zero();
+
+    for row in 0..manifold.height() {
+        for (col, probability) in arriving.iter_mut().enumerate() {
+            if manifold[(row, col)] == 'S' {
+                *probability += &scale;
+            }
+        }
+
+        let has_splitter =
+            (0..width).any(|col| is_splitter(manifold[(row, col)]) && !arriving[col].is_zero());
+        if !has_splitter {
+            continue;
+        }
+        scale = &scale * &ten;
+        lost = &lost * &ten;
+        let mut next = vec![BigUint::zero(); width];
+        for (col, probability) in arriving.into_iter().enumerate() {
+            let cell = manifold[(row, col)];
+            if !is_splitter(cell) {
+                next[col] += &(&probability * &ten);
+                continue;
+            }
+            let left = &probability * &BigUint::from(left_tenths(cell));
+            let right = &probability * &BigUint::from(10 - left_tenths(cell));
+            match col.checked_sub(1) {
+                Some(left_col) => next[left_col] += &left,
+                None => lost += &left,
+            }
+            match next.get_mut(col + 1) {
+                Some(right_cell) => *right_cell += &right,
+                None => lost += &right,
+            }
+        }
+        arriving = next;
+    }
+
+    let denominator = &BigUint::from(nb_sources as u64) * &scale;
+    ExitDistribution {
+        exits: arriving
+            .into_iter()
+            .map(|numerator| Rational::new(numerator, denominator.clone()))
+            .collect(),
+        lost: Rational::new(lost, denominator),
+    }
+}
@@ -0,0 +1,43 @@
+use std::fmt::Display;
+
+use common::BigUint;
+
+// Non-negative fraction, always kept in lowest terms.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rational {
+    numerator: BigUint,
+    denominator: BigUint,
+}
+
+impl Rational {
+    pub fn new(numerator: BigUint, denominator: BigUint) -> Self {
+        if denominator.is_zero() {
+            panic!("Rational {numerator}/0 has a zero denominator");
+        }
+        let divisor = numerator.gcd(&denominator);
+        let Some((numerator, _)) = numerator.checked_div_rem(&divisor) else {
+            panic!("Can't reduce a rational by a zero divisor");
+        };
+        let Some((denominator, _)) = denominator.checked_div_rem(&divisor) else {
+            panic!("Can't reduce a rational by a zero divisor");
+        };
+        Self {
+            numerator,
+            denominator,
+        }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.numerator.is_zero()
+    }
+}
+
+impl Display for Rational {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.denominator == BigUint::one() {
+            write!(f, "{}", self.numerator)
+        } else {
+            write!(f, "{}/{}", self.numerator, self.denominator)
+        }
+    }
+}
@@ -0,0 +1,15 @@
+// Splitters of the manifold: `^` sends the beams reaching it to both of its
+// sides evenly, a digit `d` is a weighted splitter sending them to its left
+// with probability d/10 and to its right otherwise. Both split classical beams
+// and timelines the same way.
+pub fn is_splitter(cell: char) -> bool {
+    cell == '^' || cell.is_ascii_digit()
+}
+
+// Probability for a beam to go to the left of the splitter, in tenths.
+pub fn left_tenths(cell: char) -> u64 {
+    match cell.to_digit(10) {
+        Some(digit) => digit as u64,
+        None => 5,
+    }
+}
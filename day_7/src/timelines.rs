@@ -0,0 +1,66 @@
+use common::{BigUint, Grid};
+
+use crate::splitters::is_splitter;
+
+// Number of timelines of the quantum manifold, counted row by row from the
+// start: each splitter sends the timelines reaching it to both of its sides,
+// the timelines falling off the sides of the manifold being lost. Counts double
+// at each layer of splitters, they are kept exact with big integers.
+pub struct Timelines {
+    // Number of timelines reaching each cell.
+    through: Grid<BigUint>,
+    // Number of timelines leaving the manifold below each column.
+    exits: Vec<BigUint>,
+}
+
+impl Timelines {
+    pub fn count(manifold: &Grid<char>) -> Self {
+        let width = manifold.width();
+        let mut through = Grid::new(width, manifold.height(), BigUint::zero());
+        let mut arriving = vec![BigUint::zero(); width];
+        for row in 0..manifold.height() {
+            for (col, count) in arriving.iter_mut().enumerate() {
+                if manifold[(row, col)] == 'S' {
+                    *count += &BigUint::one();
+                }
+            }
+
+            let mut next = vec![BigUint::zero(); width];
+            for (col, count) in arriving.into_iter().enumerate() {
+                if count.is_zero() {
+                    continue;
+                }
+                if is_splitter(manifold[(row, col)]) {
+                    if col > 0 {
+                        next[col - 1] += &count;
+                    }
+                    if col + 1 < width {
+                        next[col + 1] += &count;
+                    }
+                } else {
+                    next[col] += &count;
+                }
+                through[(row, col)] = count;
+            }
+            arriving = next;
+        }
+
+        Self {
+            through,
+            exits: arriving,
+        }
+    }
+
+    pub fn total(&self) -> BigUint {
+        self.exits.iter().cloned().sum()
+    }
+
+    pub fn exits(&self) -> &[BigUint] {
+        &self.exits
+    }
+
+    // `None` when the cell is out of the manifold.
+    pub fn through(&self, row: usize, col: usize) -> Option<&BigUint> {
+        self.through.get(row, col)
+    }
+}